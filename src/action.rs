@@ -0,0 +1,393 @@
+//! Input actions, and their key and gamepad button bindings
+//!
+//! The keyboard and gamepad modules produce `ActionEvent`s by the bindings,
+//! turned into the game events here depending on the game state. The bindings
+//! are read from and saved to a RON file, e.g., `(keys: {Fire: [Space]})`,
+//! where left out actions keep their default bindings. They are changed on
+//! the controls screen, reached from the settings screen.
+
+use crate::{
+    common::*, config::GameConfig, game_state::*, lazer::FireLazerEvent,
+    leader_board::NameEntryEvent, pause::PauseState, player::PlayerEvent, SpaceSet,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io, path::PathBuf};
+
+/// Input actions, with `path` the bindings file (default bindings only if None)
+pub struct ActionPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Default for ActionPlugin {
+    fn default() -> Self {
+        ActionPlugin {
+            path: data_dir().map(|dir| dir.join("bindings.ron")),
+        }
+    }
+}
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        let bindings = match &self.path {
+            Some(path) => Bindings::load(path.clone()),
+            None => Bindings::default(),
+        };
+        app.insert_resource(bindings)
+            .init_resource::<Rebinding>()
+            .add_event::<ActionEvent>()
+            .add_event::<PlayerEvent>()
+            .add_event::<FireLazerEvent>()
+            .add_event::<GameStateEvent>()
+            .add_event::<NameEntryEvent>()
+            .add_systems(OnExit(PauseState::Controls), save_system)
+            .add_systems(
+                Update,
+                (
+                    capture_system.run_if(in_state(PauseState::Controls)),
+                    update_system,
+                    controls_system,
+                )
+                    .chain()
+                    .in_set(SpaceSet::Input),
+            );
+    }
+}
+
+/// What the player asks for, whatever the key or button
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /// slow movement, while held
+    Fine,
+    Fire,
+    /// firing for as long as held
+    AutoFire,
+    /// insert coin, a single player game
+    Start,
+    StartCoOp,
+    StartAlternating,
+    Pause,
+    ToggleInfo,
+    /// the settings screen, from the attract mode
+    Settings,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fine,
+        Action::Fire,
+        Action::AutoFire,
+        Action::Start,
+        Action::StartCoOp,
+        Action::StartAlternating,
+        Action::Pause,
+        Action::ToggleInfo,
+        Action::Settings,
+    ];
+}
+
+/// An action held by the player, sent each frame while held
+///
+/// The events are kept while the game is frozen, so each menu reads the actions
+/// in any state, clearing them unless open, not to pick up stale ones on entering.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ActionEvent {
+    pub player: usize,
+    pub action: Action,
+    /// 0..=1, analog for the stick
+    pub value: f32,
+    /// pressed this frame
+    pub just_pressed: bool,
+}
+
+impl ActionEvent {
+    /// A key or button, held or just pressed
+    pub fn button(player: usize, action: Action, just_pressed: bool) -> Self {
+        ActionEvent {
+            player,
+            action,
+            value: 1.0,
+            just_pressed,
+        }
+    }
+}
+
+/// The keys and gamepad buttons of each action
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    pub keys: BTreeMap<Action, Vec<KeyCode>>,
+    pub buttons: BTreeMap<Action, Vec<GamepadButtonType>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadButtonType as Button;
+        Bindings {
+            path: None,
+            keys: BTreeMap::from([
+                (Action::MoveLeft, vec![KeyCode::KeyA, KeyCode::ArrowLeft]),
+                (Action::MoveRight, vec![KeyCode::KeyD, KeyCode::ArrowRight]),
+                (Action::Fine, vec![KeyCode::ShiftLeft]),
+                (Action::Fire, vec![KeyCode::Space]),
+                (Action::AutoFire, vec![KeyCode::ArrowUp]),
+                (Action::Start, vec![KeyCode::Enter]),
+                (Action::StartCoOp, vec![KeyCode::Digit2]),
+                (Action::StartAlternating, vec![KeyCode::Digit3]),
+                (Action::Pause, vec![KeyCode::Escape, KeyCode::KeyP]),
+                (Action::ToggleInfo, vec![KeyCode::KeyI]),
+                (Action::Settings, vec![KeyCode::KeyC]),
+            ]),
+            buttons: BTreeMap::from([
+                (Action::MoveLeft, vec![Button::DPadLeft]),
+                (Action::MoveRight, vec![Button::DPadRight]),
+                (Action::Fine, vec![Button::LeftTrigger]),
+                (Action::Fire, vec![Button::South]),
+                (Action::Start, vec![Button::South]),
+                (Action::StartCoOp, vec![Button::Start]),
+                (Action::StartAlternating, vec![Button::Select]),
+                (Action::Pause, vec![Button::Start]),
+                (Action::ToggleInfo, vec![Button::North]),
+                (Action::Settings, vec![Button::West]),
+            ]),
+        }
+    }
+}
+
+impl Bindings {
+    /// Loads the bindings, the defaults if the file is missing or invalid
+    pub fn load(path: PathBuf) -> Self {
+        let bindings = load_data_file(&path, "bindings", Self::parse);
+        Bindings {
+            path: Some(path),
+            ..bindings
+        }
+    }
+
+    /// Parses the bindings, the left out actions keep their default bindings
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let parsed: Bindings = ron::from_str(content).map_err(invalid)?;
+        let mut bindings = Self::default();
+        bindings.keys.extend(parsed.keys);
+        bindings.buttons.extend(parsed.buttons);
+        Ok(bindings)
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("bindings should serialize")
+    }
+
+    /// Saves the bindings, if loaded from a file
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_data_file(path, &self.to_ron())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn buttons(&self, action: Action) -> &[GamepadButtonType] {
+        self.buttons.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds the key to the action, in place of its keys
+    pub fn bind_key(&mut self, action: Action, key: KeyCode) {
+        self.keys.insert(action, vec![key]);
+    }
+
+    /// Binds the button to the action, in place of its buttons
+    pub fn bind_button(&mut self, action: Action, button: GamepadButtonType) {
+        self.buttons.insert(action, vec![button]);
+    }
+}
+
+/// The action picked on the controls screen, and if waiting for its new binding
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding {
+    pub selected: usize,
+    pub capturing: bool,
+    /// bound this frame, the key or button is not taken as an action
+    just_bound: bool,
+}
+
+impl Rebinding {
+    pub fn action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+}
+
+// the actions as game events, by the game state, the menus over the game take them instead
+#[allow(clippy::too_many_arguments)]
+pub fn update_system(
+    mut action_er: EventReader<ActionEvent>,
+    mut player_ew: EventWriter<PlayerEvent>,
+    mut fire_lazer_ew: EventWriter<FireLazerEvent>,
+    mut game_state_ew: EventWriter<GameStateEvent>,
+    mut name_entry_ew: EventWriter<NameEntryEvent>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    config: Res<GameConfig>,
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+) {
+    if *pause_state.get() != PauseState::Running {
+        action_er.clear();
+        return;
+    }
+
+    // movement of each player, the strongest input of each direction
+    let mut left = [0.0f32; MAX_PLAYERS];
+    let mut right = [0.0f32; MAX_PLAYERS];
+    let mut fine = [false; MAX_PLAYERS];
+    let mut autofire = [false; MAX_PLAYERS];
+    let mut select = false;
+
+    for event in action_er.read() {
+        let player = event.player;
+        match event.action {
+            Action::MoveLeft => left[player] = left[player].max(event.value),
+            Action::MoveRight => right[player] = right[player].max(event.value),
+            Action::Fine => fine[player] = true,
+            Action::AutoFire => autofire[player] = true,
+            _ => {}
+        }
+        if !event.just_pressed {
+            continue;
+        }
+        trace!("action {:?} of player {}", event.action, player);
+        match (state.get(), event.action) {
+            (GameState::EnterName, Action::MoveLeft) => {
+                name_entry_ew.send(NameEntryEvent::Previous);
+            }
+            (GameState::EnterName, Action::MoveRight) => {
+                name_entry_ew.send(NameEntryEvent::Next);
+            }
+            // the same key or button may be bound to both
+            (GameState::EnterName, Action::Fire | Action::Start) => select = true,
+            (GameState::InsertCoin | GameState::LeaderBoard, Action::Start) => {
+                game_state_ew.send(GameStateEvent::PressPlay);
+            }
+            (GameState::InsertCoin | GameState::LeaderBoard, Action::StartCoOp) => {
+                game_state_ew.send(GameStateEvent::PressPlayCoOp);
+            }
+            (GameState::InsertCoin | GameState::LeaderBoard, Action::StartAlternating) => {
+                game_state_ew.send(GameStateEvent::PressPlayAlternating);
+            }
+            (GameState::InsertCoin | GameState::LeaderBoard, Action::Settings) => {
+                next_pause_state.set(PauseState::Settings);
+            }
+            (GameState::PlayerSpawn | GameState::Play, Action::Fire) => {
+                debug!("-- fire lazer event sent --");
+                fire_lazer_ew.send(FireLazerEvent(player));
+            }
+            (GameState::PlayerSpawn | GameState::Play, Action::Pause) => {
+                next_pause_state.set(PauseState::Paused);
+            }
+            (_, Action::ToggleInfo) => {
+                game_state_ew.send(GameStateEvent::Info);
+            }
+            _ => {}
+        }
+    }
+
+    if select {
+        name_entry_ew.send(NameEntryEvent::Select);
+    }
+    if matches!(state.get(), GameState::PlayerSpawn | GameState::Play) {
+        for player in (0..MAX_PLAYERS).filter(|player| autofire[*player]) {
+            fire_lazer_ew.send(FireLazerEvent(player));
+        }
+    }
+    for player in 0..MAX_PLAYERS {
+        let speed = if fine[player] {
+            config.player_slow
+        } else {
+            1.0
+        };
+        let movement = (right[player] - left[player]) * speed;
+        if movement != 0.0 {
+            player_ew.send(PlayerEvent(player, movement));
+        }
+    }
+}
+
+// the controls screen, picking an action to bind, leaving on Start or Pause
+// back to the settings screen
+pub fn controls_system(
+    mut action_er: EventReader<ActionEvent>,
+    mut rebinding: ResMut<Rebinding>,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if *pause_state.get() != PauseState::Controls
+        || rebinding.capturing
+        || std::mem::take(&mut rebinding.just_bound)
+    {
+        action_er.clear();
+        return;
+    }
+    for event in action_er.read().filter(|event| event.just_pressed) {
+        match event.action {
+            Action::MoveLeft => {
+                rebinding.selected =
+                    (rebinding.selected + Action::ALL.len() - 1) % Action::ALL.len();
+            }
+            Action::MoveRight => {
+                rebinding.selected = (rebinding.selected + 1) % Action::ALL.len();
+            }
+            Action::Fire => {
+                debug!("rebinding {:?}", rebinding.action());
+                rebinding.capturing = true;
+                return;
+            }
+            Action::Start | Action::Pause => {
+                next_state.set(PauseState::Settings);
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+// the next key or button pressed is bound to the action picked
+pub fn capture_system(
+    mut bindings: ResMut<Bindings>,
+    mut rebinding: ResMut<Rebinding>,
+    // left out without the input devices
+    keyboard_input: Option<Res<ButtonInput<KeyCode>>>,
+    button_inputs: Option<Res<ButtonInput<GamepadButton>>>,
+) {
+    if !rebinding.capturing {
+        return;
+    }
+    let action = rebinding.action();
+    if let Some(key) = keyboard_input.and_then(|input| input.get_just_pressed().next().copied()) {
+        info!("{:?} bound to {:?}", action, key);
+        bindings.bind_key(action, key);
+        rebinding.capturing = false;
+        rebinding.just_bound = true;
+    } else if let Some(button) =
+        button_inputs.and_then(|input| input.get_just_pressed().next().copied())
+    {
+        info!("{:?} bound to {:?}", action, button.button_type);
+        bindings.bind_button(action, button.button_type);
+        rebinding.capturing = false;
+        rebinding.just_bound = true;
+    }
+}
+
+pub fn save_system(bindings: Res<Bindings>, mut rebinding: ResMut<Rebinding>) {
+    rebinding.capturing = false;
+    if let Err(err) = bindings.save() {
+        error!("failed to save bindings: {}", err);
+    }
+}
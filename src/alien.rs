@@ -1,16 +1,208 @@
 use crate::common::*;
 use bevy::prelude::*;
 
+use bevy::time::Stopwatch;
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::time::Duration;
 
-use crate::{common::Direction3, game_state::*, particle::*};
+use crate::{
+    audio::PlaySoundEvent,
+    common::Direction3,
+    config::GameConfig,
+    game_state::*,
+    particle::*,
+    pause::PauseState,
+    player::{self, Player},
+    rng::GameRng,
+    simulation::Interpolated,
+    wave::{enter_wave_system, CurrentWave, Wave},
+    SpaceSet,
+};
+use rand::Rng;
+use serde::Deserialize;
+
+pub struct AlienPlugin;
+
+impl Plugin for AlienPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AlienKilledEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                OnEnter(GameState::Start),
+                reset_system.after(enter_wave_system),
+            )
+            .add_systems(
+                OnEnter(GameState::NewWave),
+                reset_system.after(enter_wave_system),
+            )
+            .add_systems(
+                Update,
+                animate_update_system
+                    .run_if(in_state(PauseState::Running))
+                    .in_set(SpaceSet::Logic),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_system,
+                    descend_system.run_if(in_state(GameState::Play)),
+                    bullet_spawn_system,
+                    bullet_update_system,
+                    missile_spawn_system.run_if(in_state(GameState::Play)),
+                    missile_update_system,
+                )
+                    .chain()
+                    .in_set(SpaceSet::Logic),
+            );
+    }
+}
 
 #[derive(Component)]
 pub struct Alien {
     pub direction: Direction3,
 }
 
+/// The kind of alien, the classic rows plus tougher (armored) variants
+#[derive(Component, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AlienKind {
+    Squid,
+    Crab,
+    Octopus,
+    ArmoredCrab,
+    ArmoredOctopus,
+}
+
+/// What an alien kind may fire, when picked as the shooter
+#[derive(Clone, Copy, Debug)]
+pub struct AlienFire {
+    /// bombs, dropped from the lowest alien of a column (none if empty)
+    pub bombs: &'static [BombKind],
+    /// homing missiles, from the top row
+    pub missiles: bool,
+}
+
+impl AlienKind {
+    /// Sprite sheet of the kind, four animation frames of 64x48
+    /// (no sheets of their own yet, the kinds share one, scaled and tinted)
+    pub fn sprite_sheet(&self) -> &'static str {
+        match self {
+            AlienKind::Squid => "sprites/alien.png",
+            AlienKind::Crab | AlienKind::ArmoredCrab => "sprites/alien.png",
+            AlienKind::Octopus | AlienKind::ArmoredOctopus => "sprites/alien.png",
+        }
+    }
+
+    /// Displayed size, the sprite sheet scaled to it
+    pub fn sprite_size(&self) -> Vec2 {
+        match self {
+            AlienKind::Squid => Vec2::new(48.0, 36.0),
+            AlienKind::Crab | AlienKind::ArmoredCrab => Vec2::new(56.0, 42.0),
+            AlienKind::Octopus | AlienKind::ArmoredOctopus => Vec2::new(64.0, 48.0),
+        }
+    }
+
+    /// The tint of the sprite sheet
+    pub fn color(&self) -> Color {
+        match self {
+            AlienKind::Squid => Color::srgb(0.6, 1.0, 0.6),
+            AlienKind::Crab => Color::srgb(0.6, 0.8, 1.0),
+            AlienKind::Octopus => Color::WHITE,
+            AlienKind::ArmoredCrab => Color::srgb(0.3, 0.4, 1.0),
+            AlienKind::ArmoredOctopus => Color::srgb(0.6, 0.6, 0.6),
+        }
+    }
+
+    /// Hit box size
+    pub fn size(&self) -> Vec2 {
+        match self {
+            AlienKind::Squid => Vec2::new(48.0, 30.0),
+            AlienKind::Crab | AlienKind::ArmoredCrab => Vec2::new(56.0, 35.0),
+            AlienKind::Octopus | AlienKind::ArmoredOctopus => ALIEN_SIZE,
+        }
+    }
+
+    pub fn score(&self, config: &GameConfig) -> u32 {
+        let multiple = match self {
+            AlienKind::Squid => 3,
+            AlienKind::Crab => 2,
+            AlienKind::Octopus => 1,
+            AlienKind::ArmoredCrab => 4,
+            AlienKind::ArmoredOctopus => 3,
+        };
+        multiple * config.score_alien
+    }
+
+    /// Lazer hits needed to kill the alien
+    pub fn hit_points(&self) -> u8 {
+        match self {
+            AlienKind::ArmoredCrab | AlienKind::ArmoredOctopus => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn fire(&self) -> AlienFire {
+        let bombs: &'static [BombKind] = match self {
+            AlienKind::Squid => &[BombKind::Squiggly, BombKind::Plunger],
+            AlienKind::Crab => &[BombKind::Straight, BombKind::Plunger, BombKind::Squiggly],
+            AlienKind::Octopus => &[BombKind::Straight, BombKind::ZigZag],
+            AlienKind::ArmoredCrab => &[BombKind::Plunger, BombKind::Squiggly],
+            AlienKind::ArmoredOctopus => &[BombKind::ZigZag, BombKind::Plunger],
+        };
+        AlienFire {
+            bombs,
+            missiles: *self == AlienKind::Squid,
+        }
+    }
+}
+
+/// The kind of bomb dropped, picked among those of the alien kind enabled by the wave
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BombKind {
+    /// straight down, the classic bullet
+    Straight,
+    /// slow, swaying from side to side
+    ZigZag,
+    /// fast, straight down
+    Plunger,
+    /// rolling, dropped above the player and drifting towards its column
+    Squiggly,
+}
+
+impl BombKind {
+    /// Speed, relative to the alien bullet speed of the wave
+    pub fn speed(&self) -> f32 {
+        match self {
+            BombKind::Straight => 1.0,
+            BombKind::ZigZag => 0.6,
+            BombKind::Plunger => 1.6,
+            BombKind::Squiggly => 0.9,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            BombKind::Straight | BombKind::ZigZag => Color::WHITE,
+            BombKind::Plunger => Color::srgb(1.0, 0.5, 0.5),
+            BombKind::Squiggly => Color::srgb(1.0, 1.0, 0.5),
+        }
+    }
+}
+
+/// Remaining lazer hits of an alien
+#[derive(Component, Deref, DerefMut)]
+pub struct HitPoints(pub u8);
+
+/// An alien of the formation, as spawned by `spawn_borrowed`
+#[derive(Clone, Copy, Debug)]
+pub struct AlienSnapshot {
+    pub kind: AlienKind,
+    pub hit_points: u8,
+    pub translation: Vec3,
+    pub direction: Direction3,
+}
+
 #[derive(Component, Clone, Copy)]
 pub struct AnimationIndices {
     first: usize,
@@ -39,13 +231,52 @@ pub fn animate_update_system(
 #[derive(Component)]
 pub struct AlienBullet;
 
+/// The bomb kind of an alien bullet, and the time since dropped
+/// (bullets without it fall straight down)
+#[derive(Component)]
+pub struct Bomb {
+    pub kind: BombKind,
+    elapsed: f32,
+    /// dropped at, the zig-zag sways around it
+    x: f32,
+}
+
+impl Bomb {
+    pub fn new(kind: BombKind, x: f32) -> Self {
+        Bomb {
+            kind,
+            elapsed: 0.0,
+            x,
+        }
+    }
+}
+
+/// An alien was killed, at `position`
+#[derive(Event)]
+pub struct AlienKilledEvent {
+    pub position: Vec2,
+}
+
+#[allow(clippy::type_complexity)]
 pub fn bullet_update_system(
     mut commands: Commands,
     time: Res<Time>,
+    store: Res<Store>,
     image: Res<CrossImage>,
-    mut bullet_query: Query<(Entity, &mut Transform), With<AlienBullet>>,
+    player_query: Query<&Transform, (With<Player>, Without<AlienBullet>)>,
+    mut bullet_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            Option<&mut Bomb>,
+            Option<&mut Sprite>,
+        ),
+        With<AlienBullet>,
+    >,
 ) {
-    for (entity, mut transform) in &mut bullet_query {
+    let delta = time.delta_seconds();
+
+    for (entity, mut transform, bomb, sprite) in &mut bullet_query {
         if transform.translation.y < -SCENE_HEIGHT {
             trace!("bullet despawn");
             spawn_explosion(
@@ -58,36 +289,176 @@ pub fn bullet_update_system(
                 (10.0, 10.0).into(),
             );
             commands.entity(entity).despawn();
-        } else {
-            transform.translation.y -= ALIEN_BULLET_SPEED * time.delta_seconds();
+            continue;
+        }
+        let Some(mut bomb) = bomb else {
+            transform.translation.y -= store.bullet_speed * delta;
+            continue;
+        };
+        bomb.elapsed += delta;
+        transform.translation.y -= store.bullet_speed * bomb.kind.speed() * delta;
+        match bomb.kind {
+            BombKind::Straight | BombKind::Plunger => {}
+            BombKind::ZigZag => {
+                transform.translation.x = bomb.x
+                    + BOMB_ZIGZAG_AMPLITUDE * (bomb.elapsed * BOMB_ZIGZAG_FREQUENCY * TAU).sin();
+            }
+            BombKind::Squiggly => {
+                let position = transform.translation.truncate();
+                if let Some(player) = player::closest(&player_query, position) {
+                    let drift = BOMB_SQUIGGLY_DRIFT * delta;
+                    transform.translation.x += (player.x - position.x).clamp(-drift, drift);
+                }
+                if let Some(mut sprite) = sprite {
+                    sprite.flip_x = (bomb.elapsed / BOMB_ROLL_INTERVAL) as u32 % 2 == 1;
+                }
+            }
+        }
+    }
+}
+
+/// Homing missile, steering towards the player with a limited turn rate
+#[derive(Component)]
+pub struct HomingMissile {
+    velocity: Vec2,
+    lifetime: Timer,
+    particle_timer: Timer,
+}
+
+/// missile movement and trail, self destructs at the end of its lifetime
+pub fn missile_update_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    image: Res<CrossImage>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, (With<Player>, Without<HomingMissile>)>,
+    mut missile_query: Query<(Entity, &mut HomingMissile, &mut Transform)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut missile, mut transform) in &mut missile_query {
+        let position = transform.translation.truncate();
+        missile.lifetime.tick(time.delta());
+        if missile.lifetime.finished() || position.y < -SCENE_HEIGHT {
+            trace!("missile despawn");
+            spawn_explosion(
+                &mut commands,
+                &image,
+                20,
+                position,
+                200.0,
+                0.0,
+                (10.0, 10.0).into(),
+            );
+            commands.entity(entity).despawn();
+            continue;
         }
+
+        // turn towards the closest player, at most the missile turn rate
+        if let Some(target) = player::closest(&player_query, position) {
+            let max_turn = config.missile_turn_rate * delta;
+            let turn = missile
+                .velocity
+                .angle_between(target - position)
+                .clamp(-max_turn, max_turn);
+            missile.velocity = Vec2::from_angle(turn).rotate(missile.velocity);
+        }
+
+        transform.translation += (missile.velocity * delta).extend(0.0);
+        // the sprite points downwards
+        transform.rotation =
+            Quat::from_rotation_z(missile.velocity.y.atan2(missile.velocity.x) + FRAC_PI_2);
+
+        missile.particle_timer.tick(time.delta());
+        if missile.particle_timer.just_finished() {
+            spawn_particle(
+                &mut commands,
+                &image,
+                position,
+                -0.1 * missile.velocity
+                    + 30.0 * (Vec2::new(rng.cosmetic().gen(), rng.cosmetic().gen()) - 0.5),
+                (0.0, 0.0).into(),
+            );
+        }
+    }
+}
+
+/// homing missiles, fired from the top row of the formation in later waves
+pub fn missile_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut alien_resource: ResMut<AlienResource>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    current_wave: Res<CurrentWave>,
+    aliens: Query<(&Transform, &AlienKind)>,
+) {
+    if !current_wave.missiles {
+        return;
+    }
+    alien_resource.missile_spawn_timer.tick(time.delta());
+    if alien_resource.missile_spawn_timer.elapsed()
+        < Duration::from_secs_f32(config.missile_interval)
+        || rng.gameplay().gen::<f32>() >= config.missile_probability
+    {
+        return;
+    }
+
+    // any of the aliens left whose kind fires missiles, in whatever row
+    let shooters: Vec<&Transform> = aliens
+        .iter()
+        .filter(|(_, kind)| kind.fire().missiles)
+        .map(|(t, _)| t)
+        .collect();
+    if shooters.is_empty() {
+        return;
     }
+    let transform = *shooters[rng.gameplay().gen_range(0..shooters.len())];
+
+    alien_resource.missile_spawn_timer.reset();
+    trace!("missile spawned {:?}", transform.translation);
+    commands.spawn((
+        HomingMissile {
+            velocity: Vec2::new(0.0, -config.missile_speed),
+            lifetime: Timer::from_seconds(config.missile_lifetime, TimerMode::Once),
+            particle_timer: Timer::from_seconds(MISSILE_PARTICLE_INTERVAL, TimerMode::Repeating),
+        },
+        Interpolated::new(transform.translation),
+        SpriteBundle {
+            transform,
+            texture: alien_resource.missile_image_handle.clone(),
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.6, 0.2),
+                ..default()
+            },
+            ..default()
+        },
+    ));
 }
 
 #[derive(Resource)]
 pub struct AlienResource {
     image_handle: Handle<Image>,
-    bullet_spawn_timer: Instant,
+    zigzag_image_handle: Handle<Image>,
+    missile_image_handle: Handle<Image>,
+    bullet_spawn_timer: Stopwatch,
+    missile_spawn_timer: Stopwatch,
+    edge_reached: bool,
 }
 
-/// alien movement and shooting
+/// alien movement, the formation marches sideways in all game states
 pub fn update_system(
     time: Res<Time>,
     mut alien_resource: ResMut<AlienResource>,
-
     store: Res<Store>,
-    mut commands: Commands,
-
     mut aliens: Query<(&mut Alien, &mut Transform)>,
 ) {
     let mut new_direction = None;
 
     let delta = time.delta_seconds();
 
-    let mut y_min = f32::MAX;
-
     for (alien, mut transform) in &mut aliens {
-        y_min = y_min.min(transform.translation.y);
         match alien.direction {
             Direction3::Left => {
                 transform.translation.x -= store.alien_speed * delta;
@@ -107,17 +478,51 @@ pub fn update_system(
 
     // set new direction for all aliens
     if let Some(direction) = new_direction {
-        for (mut alien, mut transform) in &mut aliens {
+        for (mut alien, _) in &mut aliens {
             alien.direction = direction;
-            if store.game_state == GameState::Play && y_min > BUNKERS_Y - SCENE_HEIGHT {
-                transform.translation.y -= ALIEN_SIZE.y;
-            }
         }
     }
+    alien_resource.edge_reached = new_direction.is_some();
+}
+
+/// the formation descends one row when reaching the edge, only during Play
+pub fn descend_system(
+    alien_resource: Res<AlienResource>,
+    mut aliens: Query<&mut Transform, With<Alien>>,
+) {
+    if !alien_resource.edge_reached {
+        return;
+    }
+
+    let y_min = aliens
+        .iter()
+        .fold(f32::MAX, |y_min, t| y_min.min(t.translation.y));
+
+    if y_min > BUNKERS_Y - SCENE_HEIGHT {
+        for mut transform in &mut aliens {
+            transform.translation.y -= ALIEN_SIZE.y;
+        }
+    }
+}
+
+/// alien shooting, bombs are dropped from the lowest alien of a column
+#[allow(clippy::too_many_arguments)]
+pub fn bullet_spawn_system(
+    mut commands: Commands,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    time: Res<Time>,
+    mut alien_resource: ResMut<AlienResource>,
+    mut rng: ResMut<GameRng>,
+    store: Res<Store>,
+    current_wave: Res<CurrentWave>,
+    player_query: Query<&Transform, With<Player>>,
+    aliens: Query<(&Transform, &AlienKind)>,
+) {
+    alien_resource.bullet_spawn_timer.tick(time.delta());
 
     // calculate the lowest y value among aliens (lowest row)
     let mut hm = HashMap::new();
-    aliens.iter().for_each(|(_, t)| {
+    aliens.iter().for_each(|(t, _)| {
         let Vec3 { x, y, z: _ } = t.translation;
         let x = x as i32;
         if let Some(y_min) = hm.get(&x) {
@@ -129,28 +534,71 @@ pub fn update_system(
         }
     });
 
-    // filter out candidates at lowest row for each column
-    let mut aliens = aliens.iter_mut().filter(|(_, t)| {
+    // filter out candidates at lowest row for each column, allowed to fire bullets
+    let aliens = aliens.iter().filter(|(t, kind)| {
         let Vec3 { x, y, z: _ } = t.translation;
         let x = x as i32;
-        &y == hm.get(&x).unwrap()
+        &y == hm.get(&x).unwrap() && !kind.fire().bombs.is_empty()
     });
 
-    for (_, transform) in &mut aliens {
+    for (transform, kind) in aliens {
         // drop bullet?
         if alien_resource.bullet_spawn_timer.elapsed()
             > Duration::from_secs_f32(store.bullet_interval)
-            && rand::random::<f32>() < 1.0f32 / (hm.len() as f32)
+            && rng.gameplay().gen::<f32>() < 1.0f32 / (hm.len() as f32)
         {
-            alien_resource.bullet_spawn_timer = Instant::now();
-            trace!("bullet spawned {:?}", alien_resource.bullet_spawn_timer);
-            let texture = alien_resource.image_handle.clone();
+            alien_resource.bullet_spawn_timer.reset();
+            // the kinds of bombs of the alien enabled by the wave, else the classic bullet
+            let bombs: Vec<BombKind> = kind
+                .fire()
+                .bombs
+                .iter()
+                .filter(|bomb| current_wave.bombs.contains(bomb))
+                .copied()
+                .collect();
+            let bomb = if bombs.is_empty() {
+                BombKind::Straight
+            } else {
+                bombs[rng.gameplay().gen_range(0..bombs.len())]
+            };
+
+            // as the arcade rolling shot, dropped from the column closest to a player
+            let mut transform = *transform;
+            if bomb == BombKind::Squiggly {
+                let distance = |x: i32| {
+                    player_query
+                        .iter()
+                        .map(|player| (x as f32 - player.translation.x).abs())
+                        .fold(f32::MAX, f32::min)
+                };
+                if let Some((x, y)) = hm
+                    .iter()
+                    .min_by(|(a, _), (b, _)| distance(**a).total_cmp(&distance(**b)).then(a.cmp(b)))
+                {
+                    transform.translation.x = *x as f32;
+                    transform.translation.y = *y;
+                }
+            }
+            trace!("bomb {:?} spawned {:?}", bomb, transform.translation);
+            play_sound_ew.send(PlaySoundEvent::AlienBomb);
+            let texture = match bomb {
+                BombKind::Straight | BombKind::Plunger => alien_resource.image_handle.clone(),
+                BombKind::ZigZag => alien_resource.zigzag_image_handle.clone(),
+                BombKind::Squiggly => alien_resource.missile_image_handle.clone(),
+            };
 
             commands.spawn((
                 AlienBullet,
+                Bomb::new(bomb, transform.translation.x),
+                Interpolated::new(transform.translation),
                 SpriteBundle {
-                    transform: *transform,
+                    transform,
                     texture,
+                    sprite: Sprite {
+                        color: bomb.color(),
+                        custom_size: (bomb == BombKind::Squiggly).then_some(BOMB_SQUIGGLY_SIZE),
+                        ..default()
+                    },
                     ..default()
                 },
             ));
@@ -158,44 +606,90 @@ pub fn update_system(
     }
 }
 
+/// The alien formation of the wave, before the first move
+pub fn formation(wave: &Wave) -> Vec<AlienSnapshot> {
+    let mut aliens = vec![];
+    let step_x = ALIENS_SPACE;
+    let step_y = ALIENS_SPACE * 0.75;
+    for (y, kind) in wave.rows.iter().enumerate() {
+        for x in 0..wave.columns {
+            aliens.push(AlienSnapshot {
+                kind: *kind,
+                hit_points: kind.hit_points(),
+                translation: Vec3::new(
+                    (x as f32 - wave.columns as f32 / 2.0) * step_x,
+                    SCENE_HEIGHT - wave.start_height - (y as f32 * step_y),
+                    -1.0, // behind in scene
+                ),
+                direction: Direction3::Right,
+            });
+        }
+    }
+    aliens
+}
+
 // Builds and spawns the Alien sprites
 pub fn setup_borrowed(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    wave: &Wave,
 ) {
-    // Builds and spawns the Alien sprites
-    let texture = asset_server.load("sprites/alien.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::new(64, 48), 4, 1, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let animation_indices = AnimationIndices { first: 0, last: 3 };
+    spawn_borrowed(
+        commands,
+        asset_server,
+        texture_atlas_layouts,
+        &formation(wave),
+    );
+}
 
+// Spawns the Alien sprites of a formation, e.g., as saved at the end of a turn
+pub fn spawn_borrowed(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    formation: &[AlienSnapshot],
+) {
+    // the sprite sheet of each kind, shared by its aliens
+    let mut sheets = HashMap::new();
     let mut aliens = vec![];
-    let step_x = ALIENS_SPACE;
-    let step_y = ALIENS_SPACE * 0.75;
-    for y in 0..ALIENS_ROW {
-        for x in 0..ALIENS_COL {
-            aliens.push((
-                Alien {
-                    direction: Direction3::Right,
-                },
-                SpriteBundle {
-                    transform: Transform::from_xyz(
-                        (x as f32 - ALIENS_COL as f32 / 2.0) * step_x,
-                        SCENE_HEIGHT - 100.0 - (y as f32 * step_y),
-                        -1.0, // behind in scene
-                    ),
-                    texture: texture.clone(),
+    for alien in formation {
+        let (texture, texture_atlas_layout) = sheets
+            .entry(alien.kind)
+            .or_insert_with(|| {
+                let layout = TextureAtlasLayout::from_grid(UVec2::new(64, 48), 4, 1, None, None);
+                (
+                    asset_server.load(alien.kind.sprite_sheet()),
+                    texture_atlas_layouts.add(layout),
+                )
+            })
+            .clone();
+        let animation_indices = AnimationIndices { first: 0, last: 3 };
+        let transform = Transform::from_translation(alien.translation);
+        aliens.push((
+            Alien {
+                direction: alien.direction,
+            },
+            alien.kind,
+            HitPoints(alien.hit_points),
+            Interpolated::new(transform.translation),
+            SpriteBundle {
+                transform,
+                texture,
+                sprite: Sprite {
+                    color: alien.kind.color(),
+                    custom_size: Some(alien.kind.sprite_size()),
                     ..default()
                 },
-                TextureAtlas {
-                    layout: texture_atlas_layout.clone(),
-                    index: animation_indices.first,
-                },
-                animation_indices,
-                AnimationTimer(Timer::from_seconds(0.05, TimerMode::Repeating)),
-            ));
-        }
+                ..default()
+            },
+            TextureAtlas {
+                layout: texture_atlas_layout,
+                index: animation_indices.first,
+            },
+            animation_indices,
+            AnimationTimer(Timer::from_seconds(0.05, TimerMode::Repeating)),
+        ));
     }
     commands.spawn_batch(aliens);
 }
@@ -204,23 +698,42 @@ pub fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    current_wave: Res<CurrentWave>,
 ) {
-    setup_borrowed(&mut commands, &asset_server, &mut texture_atlas_layouts);
+    setup_borrowed(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        &current_wave,
+    );
     // Loads bullet sprite and store resource
     commands.insert_resource(AlienResource {
         image_handle: asset_server.load("sprites/drop.png"),
-        bullet_spawn_timer: Instant::now(),
+        zigzag_image_handle: asset_server.load("sprites/drop_old.png"),
+        missile_image_handle: asset_server.load("sprites/drop2.png"),
+        bullet_spawn_timer: Stopwatch::new(),
+        missile_spawn_timer: Stopwatch::new(),
+        edge_reached: false,
     })
 }
-// reset the aliens
-pub fn reset(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+// reset the aliens, on entering Start and NewWave
+pub fn reset_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    current_wave: Res<CurrentWave>,
     alien_query: Query<Entity, With<Alien>>,
     alien_bullet_query: Query<Entity, With<AlienBullet>>,
+    missile_query: Query<Entity, With<HomingMissile>>,
 ) {
+    let commands = &mut commands;
     cleanup_state(commands, alien_query);
     cleanup_state(commands, alien_bullet_query);
-    setup_borrowed(commands, asset_server, texture_atlas_layout);
+    cleanup_state(commands, missile_query);
+    setup_borrowed(
+        commands,
+        &asset_server,
+        &mut texture_atlas_layout,
+        &current_wave,
+    );
 }
@@ -0,0 +1,166 @@
+//! Alternating two-player, the classic arcade turns
+//!
+//! The players take turns on a single ship, handed over on each life lost. The
+//! wave state of each player (the surviving aliens and their formation, the
+//! bunker damage and the wave progress) is saved at the end of a turn, and
+//! restored at the start of the next turn of that player. The scores and lives
+//! are kept by player in the `Store`.
+
+use crate::{
+    alien::{self, Alien, AlienBullet, AlienKind, AlienSnapshot, HitPoints, HomingMissile},
+    bunker::{self, Bunker, BunkerSnapshot},
+    difficulty::enter_wave_difficulty_system,
+    game_state::*,
+    lazer::Lazer,
+    player::{self, Player},
+    wave::{CurrentWave, Wave},
+};
+use bevy::prelude::*;
+
+pub struct AlternatingPlugin;
+
+impl Plugin for AlternatingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Turns>()
+            .add_systems(
+                OnEnter(GameState::Start),
+                start_system.after(enter_wave_difficulty_system),
+            )
+            .add_systems(OnEnter(GameState::Turn), swap_system);
+    }
+}
+
+/// The wave state of a player, between turns
+#[derive(Clone, Debug)]
+pub struct WaveSnapshot {
+    pub wave: u8,
+    pub current_wave: Wave,
+    pub aliens_killed: u8,
+    pub alien_speed: f32,
+    pub bullet_interval: f32,
+    pub bullet_speed: f32,
+    pub aliens: Vec<AlienSnapshot>,
+    pub bunkers: Vec<BunkerSnapshot>,
+}
+
+impl WaveSnapshot {
+    fn new(
+        store: &Store,
+        current_wave: &Wave,
+        aliens: Vec<AlienSnapshot>,
+        bunkers: Vec<BunkerSnapshot>,
+    ) -> Self {
+        WaveSnapshot {
+            wave: store.wave,
+            current_wave: current_wave.clone(),
+            aliens_killed: store.aliens_killed,
+            alien_speed: store.alien_speed,
+            bullet_interval: store.bullet_interval,
+            bullet_speed: store.bullet_speed,
+            aliens,
+            bunkers,
+        }
+    }
+
+    fn restore(&self, store: &mut Store, current_wave: &mut CurrentWave) {
+        store.wave = self.wave;
+        store.aliens_killed = self.aliens_killed;
+        store.alien_speed = self.alien_speed;
+        store.bullet_interval = self.bullet_interval;
+        store.bullet_speed = self.bullet_speed;
+        current_wave.0 = self.current_wave.clone();
+    }
+}
+
+/// The saved wave state of each player, empty unless taking turns
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct Turns(pub Vec<WaveSnapshot>);
+
+// a new game, each player starts from the first wave as set up for the first player
+pub fn start_system(store: Res<Store>, current_wave: Res<CurrentWave>, mut turns: ResMut<Turns>) {
+    turns.clear();
+    if !store.alternating {
+        return;
+    }
+    let start = WaveSnapshot::new(
+        &store,
+        &current_wave,
+        alien::formation(&current_wave),
+        bunker::formation(current_wave.bunkers),
+    );
+    turns.resize(store.nr_players, start);
+}
+
+// the wave of the player leaving is saved, and the wave of the next player restored
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn swap_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut store: ResMut<Store>,
+    mut current_wave: ResMut<CurrentWave>,
+    mut turns: ResMut<Turns>,
+    alien_query: Query<(Entity, &Alien, &AlienKind, &HitPoints, &Transform)>,
+    bunker_query: Query<(Entity, &Transform, &TextureAtlas), With<Bunker>>,
+    mut player_query: Query<(&mut Player, &mut Sprite)>,
+    alien_bullet_query: Query<Entity, With<AlienBullet>>,
+    missile_query: Query<Entity, With<HomingMissile>>,
+    lazer_query: Query<Entity, With<Lazer>>,
+) {
+    let commands = &mut commands;
+    let turn = store.turn;
+    let next = (turn + 1) % store.nr_players;
+    debug!("player {} leaves the turn to player {}", turn, next);
+
+    turns[turn] = WaveSnapshot::new(
+        &store,
+        &current_wave,
+        alien_query
+            .iter()
+            .map(|(_, alien, kind, hit_points, transform)| AlienSnapshot {
+                kind: *kind,
+                hit_points: **hit_points,
+                translation: transform.translation,
+                direction: alien.direction,
+            })
+            .collect(),
+        bunker_query
+            .iter()
+            .map(|(_, transform, atlas)| BunkerSnapshot {
+                translation: transform.translation,
+                index: atlas.index,
+            })
+            .collect(),
+    );
+
+    for (entity, ..) in &alien_query {
+        commands.entity(entity).despawn();
+    }
+    for (entity, ..) in &bunker_query {
+        commands.entity(entity).despawn();
+    }
+    cleanup_state(commands, alien_bullet_query);
+    cleanup_state(commands, missile_query);
+    cleanup_state(commands, lazer_query);
+
+    let snapshot = &turns[next];
+    snapshot.restore(&mut store, &mut current_wave);
+    alien::spawn_borrowed(
+        commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        &snapshot.aliens,
+    );
+    bunker::spawn_borrowed(
+        commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        &snapshot.bunkers,
+    );
+
+    store.turn = next;
+    for (mut player, mut sprite) in &mut player_query {
+        *player = Player(next);
+        sprite.color = player::color(next);
+    }
+}
@@ -1,29 +1,220 @@
-//! This example illustrates how to load and play an audio file, and control how it's played.
+//! Sound effects and background music
+//!
+//! The sound effects are described in `assets/sounds.ron`, the sample and the
+//! playback speed and volume of each `PlaySoundEvent`. The samples are loaded
+//! along with the manifest into the `AudioResource`, the sounds left out (or
+//! played before the manifest is loaded) are silent.
+//!
+//! The audio is placeholder: the sounds are a single collision sample played at
+//! different pitches, until dedicated samples are made.
 
-use bevy::prelude::*;
+use crate::{pause::PauseState, settings::Settings, SpaceSet};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    audio::Volume,
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use std::io;
+
+/// Sound effects and background music
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySoundEvent>()
+            .add_event::<PlayMusicEvent>()
+            .init_resource::<MusicOnHold>()
+            .init_resource::<AudioResource>()
+            .init_asset::<SoundBank>()
+            .register_asset_loader(SoundBankLoader)
+            .add_systems(Startup, setup)
+            .add_systems(OnExit(PauseState::Running), hold_music_system)
+            .add_systems(OnEnter(PauseState::Running), resume_music_system)
+            .add_systems(
+                Update,
+                (
+                    loaded_system,
+                    audio_hit_system,
+                    play_music_system,
+                    music_volume_system,
+                )
+                    .chain()
+                    .in_set(SpaceSet::Events),
+            );
+    }
+}
 
 /// Play a one shot sound sample
-#[derive(Event)]
+#[derive(Event, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum PlaySoundEvent {
     AlienHit,
+    MysteryShipHit,
+    PowerUp,
+    PlayerFire,
+    PlayerDeath,
+    BunkerHit,
+    /// an alien bomb dropped
+    AlienBomb,
+    /// the last alien of the wave killed
+    WaveClear,
+    ExtraLife,
+    InsertCoin,
+    /// the mystery ship appearing
+    MysteryShip,
+    /// the four notes of the marching beat, in order
+    March1,
+    March2,
+    March3,
+    March4,
+}
+
+impl PlaySoundEvent {
+    pub const MARCH: [PlaySoundEvent; 4] = [
+        PlaySoundEvent::March1,
+        PlaySoundEvent::March2,
+        PlaySoundEvent::March3,
+        PlaySoundEvent::March4,
+    ];
+
+    pub const ALL: [PlaySoundEvent; 15] = [
+        PlaySoundEvent::AlienHit,
+        PlaySoundEvent::MysteryShipHit,
+        PlaySoundEvent::PowerUp,
+        PlaySoundEvent::PlayerFire,
+        PlaySoundEvent::PlayerDeath,
+        PlaySoundEvent::BunkerHit,
+        PlaySoundEvent::AlienBomb,
+        PlaySoundEvent::WaveClear,
+        PlaySoundEvent::ExtraLife,
+        PlaySoundEvent::InsertCoin,
+        PlaySoundEvent::MysteryShip,
+        PlaySoundEvent::March1,
+        PlaySoundEvent::March2,
+        PlaySoundEvent::March3,
+        PlaySoundEvent::March4,
+    ];
 }
 
 /// Control continuous playback
 #[derive(Event, Debug)]
 pub struct PlayMusicEvent(pub bool);
 
-#[derive(Resource, Clone)]
+/// A sound, as described in the sounds manifest
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SoundEntry {
+    /// the sample, relative to the assets directory
+    pub file: String,
+    #[serde(default = "SoundEntry::default_one")]
+    pub speed: f32,
+    /// 0..=1, scaled by the sound effects volume setting
+    #[serde(default = "SoundEntry::default_one")]
+    pub volume: f32,
+}
+
+impl SoundEntry {
+    fn default_one() -> f32 {
+        1.0
+    }
+}
+
+/// The sounds manifest
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SoundManifest {
+    pub sounds: HashMap<PlaySoundEvent, SoundEntry>,
+}
+
+impl SoundManifest {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let manifest: SoundManifest = ron::de::from_bytes(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        for (sound, entry) in &manifest.sounds {
+            if entry.speed <= 0.0 || !(0.0..=1.0).contains(&entry.volume) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("sound {:?} has an invalid speed or volume", sound),
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// A loaded sound, the sample and how it is played
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub source: Handle<AudioSource>,
+    pub speed: f32,
+    pub volume: f32,
+}
+
+/// The sounds manifest, along with its samples
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct SoundBank {
+    pub samples: HashMap<PlaySoundEvent, Sample>,
+}
+
+#[derive(Resource)]
+pub struct SoundBankHandle(Handle<SoundBank>);
+
+#[derive(Default)]
+pub struct SoundBankLoader;
+
+impl AssetLoader for SoundBankLoader {
+    type Asset = SoundBank;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let manifest = SoundManifest::parse(&bytes)?;
+        let samples = manifest
+            .sounds
+            .into_iter()
+            .map(|(sound, entry)| {
+                let sample = Sample {
+                    source: load_context.load(entry.file),
+                    speed: entry.speed,
+                    volume: entry.volume,
+                };
+                (sound, sample)
+            })
+            .collect();
+        Ok(SoundBank { samples })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// The sound effect samples, by sound
+#[derive(Resource, Default, Clone)]
 pub struct AudioResource {
-    hit_sample: Handle<AudioSource>,
+    samples: HashMap<PlaySoundEvent, Sample>,
 }
 
 #[derive(Component)]
 pub struct Music;
 
+/// The music was playing when the game was paused
+#[derive(Resource, Default)]
+pub struct MusicOnHold(bool);
+
 pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Sound
-    let hit_sample = asset_server.load("sounds/breakout_collision.ogg");
-    commands.insert_resource(AudioResource { hit_sample });
+    commands.insert_resource(SoundBankHandle(
+        asset_server.load::<SoundBank>("sounds.ron"),
+    ));
 
     commands.spawn((
         Music,
@@ -34,29 +225,54 @@ pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
+// the samples in use are replaced once the manifest is loaded, and on each change of it
+pub fn loaded_system(
+    mut asset_er: EventReader<AssetEvent<SoundBank>>,
+    handle: Res<SoundBankHandle>,
+    assets: Res<Assets<SoundBank>>,
+    mut sound: ResMut<AudioResource>,
+) {
+    for event in asset_er.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(loaded) = assets.get(&handle.0) {
+                info!("sounds loaded, {} samples", loaded.samples.len());
+                sound.samples = loaded.samples.clone();
+            }
+        }
+    }
+}
+
 pub fn audio_hit_system(
     mut commands: Commands,
     mut play_sound_er: EventReader<PlaySoundEvent>,
     sound: Res<AudioResource>,
+    settings: Res<Settings>,
 ) {
     for event in play_sound_er.read() {
-        let sample = match event {
-            PlaySoundEvent::AlienHit => &sound.hit_sample,
+        let Some(sample) = sound.samples.get(event) else {
+            trace!("no sample for {:?}", event);
+            continue;
         };
         commands.spawn(AudioBundle {
-            source: sample.clone(), // this is ugly, why owned?
-            settings: PlaybackSettings::DESPAWN,
+            source: sample.source.clone(), // this is ugly, why owned?
+            settings: PlaybackSettings::DESPAWN
+                .with_speed(sample.speed)
+                .with_volume(Volume::new(sample.volume * settings.sfx_volume())),
         });
     }
 }
 
+// the music sink is missing until its source is loaded, or without audio output
 pub fn play_music_system(
     mut play_music_events: EventReader<PlayMusicEvent>,
     mut music_controller_query: Query<&mut AudioSink, With<Music>>,
 ) {
     for event in play_music_events.read() {
         debug!("play_music_event {:?}", event);
-        let sink = music_controller_query.single_mut();
+        let Ok(sink) = music_controller_query.get_single_mut() else {
+            debug!("no music sink");
+            continue;
+        };
         if event.0 {
             sink.play();
         } else {
@@ -65,6 +281,41 @@ pub fn play_music_system(
     }
 }
 
+// the music sink is created once its source is loaded, so the volume is kept up to date
+pub fn music_volume_system(
+    settings: Res<Settings>,
+    music_controller_query: Query<&AudioSink, With<Music>>,
+) {
+    if let Ok(sink) = music_controller_query.get_single() {
+        if sink.volume() != settings.music_volume() {
+            sink.set_volume(settings.music_volume());
+        }
+    }
+}
+
+// the music is paused along with the game, and resumed if it was playing
+pub fn hold_music_system(
+    music_controller_query: Query<&AudioSink, With<Music>>,
+    mut on_hold: ResMut<MusicOnHold>,
+) {
+    let Ok(sink) = music_controller_query.get_single() else {
+        return;
+    };
+    on_hold.0 = !sink.is_paused();
+    sink.pause();
+}
+
+pub fn resume_music_system(
+    music_controller_query: Query<&AudioSink, With<Music>>,
+    mut on_hold: ResMut<MusicOnHold>,
+) {
+    if let Ok(sink) = music_controller_query.get_single() {
+        if std::mem::take(&mut on_hold.0) {
+            sink.play();
+        }
+    }
+}
+
 // example snippets
 // fn update_speed(music_controller: Query<&AudioSink, With<Music>>, time: Res<Time>) {
 //     if let Ok(sink) = music_controller.get_single() {
@@ -1,6 +1,26 @@
-use crate::{common::*, game_state::*};
+use crate::{
+    common::*,
+    game_state::*,
+    wave::{enter_wave_system, CurrentWave},
+};
 use bevy::prelude::*;
 
+pub struct BunkerPlugin;
+
+impl Plugin for BunkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(
+                OnEnter(GameState::Start),
+                reset_system.after(enter_wave_system),
+            )
+            .add_systems(
+                OnEnter(GameState::NewWave),
+                reset_system.after(enter_wave_system),
+            );
+    }
+}
+
 #[derive(Component, Clone, Copy)]
 pub struct Bunker;
 
@@ -13,16 +33,15 @@ pub fn hit_bunker(commands: &mut Commands, entity: Entity, mut atlas: Mut<Textur
     }
 }
 
-pub fn setup_borrowed(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
-) {
-    // Builds and spawns the bunker sprites
-    let texture = asset_server.load("sprites/defense.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(16), 5, 3, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
+/// A bunker block, its sprite index showing the damage
+#[derive(Clone, Copy, Debug)]
+pub struct BunkerSnapshot {
+    pub translation: Vec3,
+    pub index: usize,
+}
 
+/// The undamaged bunkers
+pub fn formation(bunkers: usize) -> Vec<BunkerSnapshot> {
     // The sprite index layout of the bunker
     let bunker_matrix = [
         [0, 1, 1, 1, 1, 2],
@@ -31,50 +50,101 @@ pub fn setup_borrowed(
         [1, 3, 5, 5, 4, 1],
     ];
 
-    for b in 0..BUNKERS {
-        let mut bunker = vec![];
+    // spread over the scene width, whatever the number
+    let space = SCENE_WIDTH / bunkers.max(1) as f32;
+    let mut blocks = vec![];
+    for b in 0..bunkers {
         for (r, row) in bunker_matrix.iter().enumerate() {
             for (c, data) in row.iter().enumerate() {
                 if *data < 5 {
-                    bunker.push((
-                        Bunker,
-                        SpriteBundle {
-                            transform: Transform::from_xyz(
-                                (c as f32 - (row.len() as f32 - 1.0) / 2.0) * 16.0
-                                    + (2.0 * b as f32 - (BUNKERS as f32 - 1.0)) * BUNKER_SPACE,
-                                BUNKERS_Y - SCENE_HEIGHT - (r as f32) * 16.0,
-                                0.0,
-                            ),
-                            texture: texture.clone(),
-                            ..default()
-                        },
-                        TextureAtlas {
-                            layout: texture_atlas_layout.clone(),
-                            index: *data,
-                        },
-                    ));
+                    blocks.push(BunkerSnapshot {
+                        translation: Vec3::new(
+                            (c as f32 - (row.len() as f32 - 1.0) / 2.0) * 16.0
+                                + (2.0 * b as f32 - (bunkers as f32 - 1.0)) * space,
+                            BUNKERS_Y - SCENE_HEIGHT - (r as f32) * 16.0,
+                            0.0,
+                        ),
+                        index: *data,
+                    });
                 }
             }
         }
-        commands.spawn_batch(bunker);
     }
+    blocks
+}
+
+pub fn setup_borrowed(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    bunkers: usize,
+) {
+    spawn_borrowed(
+        commands,
+        asset_server,
+        texture_atlas_layouts,
+        &formation(bunkers),
+    );
+}
+
+// Builds and spawns the bunker sprites, e.g., as saved at the end of a turn
+pub fn spawn_borrowed(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    blocks: &[BunkerSnapshot],
+) {
+    let texture = asset_server.load("sprites/defense.png");
+    let layout = TextureAtlasLayout::from_grid(UVec2::splat(16), 5, 3, None, None);
+    let texture_atlas_layout = texture_atlas_layouts.add(layout);
+
+    let bunker: Vec<_> = blocks
+        .iter()
+        .map(|block| {
+            (
+                Bunker,
+                SpriteBundle {
+                    transform: Transform::from_translation(block.translation),
+                    texture: texture.clone(),
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: texture_atlas_layout.clone(),
+                    index: block.index,
+                },
+            )
+        })
+        .collect();
+    commands.spawn_batch(bunker);
 }
 
 pub fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    current_wave: Res<CurrentWave>,
 ) {
-    setup_borrowed(&mut commands, &asset_server, &mut texture_atlas_layouts);
+    setup_borrowed(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        current_wave.bunkers,
+    );
 }
 
-pub fn reset(
-    // reset the bunkers
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+// reset the bunkers, on entering Start and NewWave
+pub fn reset_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    current_wave: Res<CurrentWave>,
     bunker_query: Query<Entity, With<Bunker>>,
 ) {
-    cleanup_state(commands, bunker_query);
-    setup_borrowed(commands, asset_server, texture_atlas_layout);
+    cleanup_state(&mut commands, bunker_query);
+    setup_borrowed(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layout,
+        current_wave.bunkers,
+    );
 }
@@ -1,4 +1,8 @@
 use bevy::prelude::*;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
 
 // vintage television format
 pub const RES_Y: f32 = 1080.0; // well a bit too modern
@@ -6,16 +10,24 @@ pub const RES_X: f32 = RES_Y * 4.0 / 3.0;
 
 pub const LEFT_STICK_HYSTERESIS: f32 = 0.01; // tune threshold to avoid drift
 
+// Settings related
+pub const VOLUME_STEP: f32 = 0.1; // 0..=1, in 10 steps
+pub const SETTINGS_SCALES: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0]; // window scale factors
+
 // Game mechanics related
-pub const PLAYER_SPEED: f32 = 500.0;
-pub const PLAYER_SLOW: f32 = 1.0 / 5.0; // The ratio for slow movement
+pub const TICK_RATE: f64 = 60.0; // Fixed timestep simulation, in Hz
 pub const PLAYER_SIZE: Vec2 = Vec2::new(64.0, 40.0);
 pub const PLAYER_HEIGHT: f32 = 50.0; // There should be a way to get this from sprite
-pub const PLAYER_SPAWN_COUNTER: u8 = 20;
-pub const PLAYER_SPAWN_DURATION: f32 = 0.1;
+pub const MAX_PLAYERS: usize = 2; // co-op
+pub const CO_OP_SPAWN_X: f32 = 200.0; // the players start either side of the center
 
-pub const LAZER_SPEED: f32 = 1250.0;
 pub const LAZER_PARTICLE_INTERVAL: f32 = 0.02; // Duration in seconds between particles
+pub const DOUBLE_CANNON_OFFSET: f32 = 16.0;
+pub const SPREAD_ANGLE: f32 = 0.25; // in radians, the side shots
+pub const LAZER_SPACING: f32 = 450.0; // the last shot clear of the cannon, for the next volley
+pub const RAPID_SPACING: f32 = 150.0; // rapid fire, a shorter one
+pub const POWER_UP_SPEED: f32 = 150.0;
+pub const POWER_UP_SIZE: Vec2 = Vec2::new(32.0, 32.0); // used for hit box
 
 pub const SCENE_WIDTH: f32 = RES_X / 2.0 - 100.0;
 pub const SCENE_HEIGHT: f32 = RES_Y / 2.0 - 50.0;
@@ -24,11 +36,23 @@ pub const ALIENS_ROW: usize = 5;
 pub const ALIENS_TOTAL: u8 = ALIENS_COL as u8 * ALIENS_ROW as u8;
 pub const ALIENS_SPACE: f32 = 80.0; // used for layout
 pub const ALIEN_SIZE: Vec2 = Vec2::new(64.0, 40.0); // used for hit box
-pub const ALIEN_BULLET_SPEED: f32 = 300.0;
 pub const ALIEN_BULLET_INTERVAL: f32 = 0.25; // in seconds
 pub const BULLET_INTERVAL_WAVE: f32 = 0.75;
+pub const MISSILE_WAVE: u8 = 3; // first wave firing homing missiles
+pub const BOMB_ZIGZAG_AMPLITUDE: f32 = 20.0; // sideways sway of the zig-zag bomb
+pub const BOMB_ZIGZAG_FREQUENCY: f32 = 2.0; // sways per second
+pub const BOMB_SQUIGGLY_DRIFT: f32 = 40.0; // sideways speed towards the player
+pub const BOMB_SQUIGGLY_SIZE: Vec2 = Vec2::new(16.0, 24.0);
+pub const BOMB_ROLL_INTERVAL: f32 = 0.1; // in seconds, the squiggly sprite is flipped
+pub const MISSILE_PARTICLE_INTERVAL: f32 = 0.05; // Duration in seconds between particles
+pub const MISSILE_SIZE: Vec2 = Vec2::new(24.0, 24.0); // used for hit box
+pub const MYSTERY_SHIP_SIZE: Vec2 = Vec2::new(96.0, 40.0); // used for hit box
+pub const MYSTERY_SHIP_Y: f32 = SCENE_HEIGHT - 40.0; // above the formation
+pub const MYSTERY_SHIP_INTERVAL: f32 = 25.0; // in seconds, between appearances
+pub const MYSTERY_SHIP_INTERVAL_WAVE: f32 = 0.9;
 pub const BUNKERS: usize = 5;
-pub const BUNKER_SPACE: f32 = SCENE_WIDTH / BUNKERS as f32;
+pub const BUNKERS_MAX: usize = 12; // fitting the scene, without overlapping
+pub const BUNKER_SPACE: f32 = SCENE_WIDTH / BUNKERS as f32; // of the default bunkers
 pub const BUNKERS_Y: f32 = 100.0;
 pub const BUNKER_SIZE: Vec2 = Vec2::new(16.0, 16.0);
 
@@ -37,31 +61,102 @@ pub const ALIENS_SPEED_KILL: f32 = 2.0;
 pub const ALIENS_SPEED_WAVE: f32 = 10.0;
 pub const ALIENS_SPEED_MAX: f32 = 100.0;
 
+pub const MARCH_STEP: f32 = 40.0; // distance the formation moves per note
+pub const MARCH_INTERVAL_MAX: f32 = 1.0; // in seconds, between notes
+pub const MARCH_INTERVAL_MIN: f32 = 0.08; // in seconds, between notes
+pub const MARCH_INTERVAL_LAST: f32 = 0.25; // interval scale with the last alien left
+
+pub const ADAPTIVE_INTERVAL: f32 = 10.0; // in seconds, between adjustments
+pub const ADAPTIVE_MIN_SHOTS: u32 = 5; // fewer shots in the interval, accuracy not judged
+pub const ADAPTIVE_ACCURACY_HIGH: f32 = 0.6; // above, the pressure increases
+pub const ADAPTIVE_ACCURACY_LOW: f32 = 0.3; // below, the pressure decreases
+pub const ADAPTIVE_STEP: f32 = 0.05;
+pub const ADAPTIVE_MIN: f32 = 0.75;
+pub const ADAPTIVE_MAX: f32 = 1.25;
+
 pub const PARTICLE_DURATION: f32 = 0.5; // in seconds
 
 // Game UI related
 pub const STATUS_BAR_FONT_SIZE: f32 = 50.0;
 pub const GAME_OVER_FONT_SIZE: f32 = 200.0;
 pub const INSERT_COIN_FONT_SIZE: f32 = 175.0;
+pub const INSERT_COIN_HINT_FONT_SIZE: f32 = 40.0; // the other games, settings and controls
 pub const NEW_WAVE_FONT_SIZE: f32 = 200.0;
 pub const START_FONT_SIZE: f32 = 200.0;
 pub const LEADER_BOARD_FONT_SIZE: f32 = 200.0;
+pub const LEADER_BOARD_TABLE_FONT_SIZE: f32 = 50.0;
+pub const NAME_ENTRY_FONT_SIZE: f32 = 150.0;
+pub const CONTROLS_FONT_SIZE: f32 = 30.0;
+pub const PAUSE_FONT_SIZE: f32 = 200.0;
+pub const PAUSE_MENU_FONT_SIZE: f32 = 60.0;
+pub const SETTINGS_FONT_SIZE: f32 = 40.0;
+pub const CONTROLLER_LABEL_FONT_SIZE: f32 = 20.0;
+pub const CONTROLLER_LABEL_OFFSET: f32 = 40.0; // below the ship
 
-pub const STATE_TRANSITION_MENU: f32 = 6.0;
-pub const STATE_TRANSITION_START: f32 = 2.0;
-pub const STATE_TRANSITION_NEW_WAVE: f32 = 1.5;
-pub const STATE_TRANSITION_SPAWN: f32 = 0.25;
-
-// Game logic related
-pub const SCORE_ALIEN: u32 = 10;
-pub const SCORE_NEW_LIFE: u32 = 1000;
-pub const SCORE_SCALE: f32 = 1.5;
-
-pub const NR_LIVES: u8 = 3;
-#[derive(Default, Clone, Copy)]
+// Game logic related, see also the GameConfig
+pub const LEADER_BOARD_ENTRIES: usize = 10;
+pub const NAME_LENGTH: usize = 3; // arcade style initials
+pub const ONLINE_RETRY_INTERVAL: f32 = 60.0; // in seconds, for queued submissions
+pub const ONLINE_TIMEOUT: f32 = 5.0; // in seconds, per request
+#[derive(Default, Clone, Copy, Debug)]
 pub enum Direction3 {
     Left,
     Right,
     #[default]
     None,
 }
+
+/// Per user data directory of the game, if one can be determined
+pub fn data_dir() -> Option<PathBuf> {
+    let dir = if cfg!(target_os = "windows") {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".local/share")))?
+    };
+    Some(dir.join("bevy-space"))
+}
+
+/// An `InvalidData` error, for malformed files
+pub fn invalid(err: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// Reads the `name` file, None if missing or unreadable (logged)
+pub fn read_data_file(path: &Path, name: &str) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("failed to read {} {:?}: {}", name, path, err);
+            }
+            None
+        }
+    }
+}
+
+/// Parses the `name` file, the default if missing or invalid (logged)
+pub fn load_data_file<T: Default>(
+    path: &Path,
+    name: &str,
+    parse: impl FnOnce(&str) -> io::Result<T>,
+) -> T {
+    let Some(content) = read_data_file(path, name) else {
+        return T::default();
+    };
+    parse(&content).unwrap_or_else(|err| {
+        warn!("failed to parse {} {:?}: {}", name, path, err);
+        T::default()
+    })
+}
+
+/// Writes the file, creating its directory
+pub fn write_data_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, content)
+}
@@ -0,0 +1,223 @@
+//! Game configuration, the gameplay tuning knobs
+//!
+//! Read from a RON file, e.g., `(nr_lives: 5, player_speed: 400.0)`, where
+//! left out settings take their default. Each setting can be overridden, by
+//! `--set <name>=<value>` on the command line, or the `BEVY_SPACE_SET_<NAME>`
+//! environment variable.
+
+use crate::{common::invalid, difficulty::Difficulty};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Prefix of the environment variables overriding a setting
+pub const CONFIG_ENV_PREFIX: &str = "BEVY_SPACE_SET_";
+
+/// Game configuration, GameConfig::default() unless set
+#[derive(Default)]
+pub struct ConfigPlugin {
+    pub config: GameConfig,
+}
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone());
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub player_speed: f32,
+    /// the ratio for slow movement
+    pub player_slow: f32,
+    /// blinks before the player is spawned
+    pub player_spawn_counter: u8,
+    pub lazer_speed: f32,
+    /// player lazers in flight at most
+    pub max_lazers: u8,
+    pub alien_bullet_speed: f32,
+    /// in seconds, at least between missiles
+    pub missile_interval: f32,
+    /// per tick, once the interval passed
+    pub missile_probability: f32,
+    pub missile_speed: f32,
+    /// in radians per second
+    pub missile_turn_rate: f32,
+    /// in seconds, then self destructs
+    pub missile_lifetime: f32,
+    pub mystery_ship_speed: f32,
+    /// no appearance with fewer aliens left
+    pub mystery_ship_min_aliens: u8,
+    /// in seconds, the state transitions
+    pub state_transition_menu: f32,
+    pub state_transition_start: f32,
+    pub state_transition_new_wave: f32,
+    pub state_transition_spawn: f32,
+    /// time out, the name entered so far is used
+    pub state_transition_name_entry: f32,
+    /// the bottom row alien, the other kinds score a multiple
+    pub score_alien: u32,
+    /// missile shot down
+    pub score_missile: u32,
+    /// first extra life
+    pub score_new_life: u32,
+    /// the next extra life, scaled from the previous
+    pub score_scale: f32,
+    pub nr_lives: u8,
+    /// a power-up dropped, per alien killed
+    pub power_up_probability: f32,
+    /// in seconds, then back to the single cannon
+    pub power_up_duration: f32,
+    pub difficulty: Difficulty,
+    /// adjust the alien speed and fire rate to the player's performance
+    pub adaptive_difficulty: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            player_speed: 500.0,
+            player_slow: 1.0 / 5.0,
+            player_spawn_counter: 20,
+            lazer_speed: 1250.0,
+            max_lazers: 5,
+            alien_bullet_speed: 300.0,
+            missile_interval: 4.0,
+            missile_probability: 0.01,
+            missile_speed: 200.0,
+            missile_turn_rate: 1.5,
+            missile_lifetime: 5.0,
+            mystery_ship_speed: 150.0,
+            mystery_ship_min_aliens: 8,
+            state_transition_menu: 6.0,
+            state_transition_start: 2.0,
+            state_transition_new_wave: 1.5,
+            state_transition_spawn: 0.25,
+            state_transition_name_entry: 30.0,
+            score_alien: 10,
+            score_missile: 20,
+            score_new_life: 100,
+            score_scale: 1.5,
+            nr_lives: 3,
+            power_up_probability: 0.05,
+            power_up_duration: 15.0,
+            difficulty: Difficulty::Normal,
+            adaptive_difficulty: false,
+        }
+    }
+}
+
+// the `(name, value)` fields of a RON struct as written by `ron::to_string`,
+// the settings holding no strings
+fn fields(ron: &str) -> Vec<(String, String)> {
+    let inner = ron
+        .strip_prefix('(')
+        .and_then(|ron| ron.strip_suffix(')'))
+        .unwrap_or_default();
+    let mut fields = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices().chain([(inner.len(), ',')]) {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                if let Some((name, value)) = inner[start..i].split_once(':') {
+                    fields.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+impl GameConfig {
+    /// Reads the config file (defaults only if None), then applies the
+    /// `(name, value)` overrides in order
+    pub fn load(path: Option<&Path>, overrides: &[(String, String)]) -> io::Result<Self> {
+        let content = match path {
+            Some(path) => fs::read_to_string(path)?,
+            None => "()".to_string(),
+        };
+        Self::parse(&content, overrides)
+    }
+
+    pub fn parse(content: &str, overrides: &[(String, String)]) -> io::Result<Self> {
+        let mut config: GameConfig = ron::from_str(content).map_err(invalid)?;
+        if !overrides.is_empty() {
+            // the overrides replace the settings as RON text, so each is read by
+            // its type (an untyped value loses the enum variants)
+            let mut settings = fields(&ron::to_string(&config).map_err(invalid)?);
+            for (name, value) in overrides {
+                // a single value, not setting others along
+                ron::from_str::<ron::Value>(value)
+                    .map_err(|err| invalid(format!("setting {}: {}", name, err)))?;
+                let Some(setting) = settings.iter_mut().find(|(field, _)| field == name) else {
+                    return Err(invalid(format!("unknown setting {}", name)));
+                };
+                setting.1 = value.clone();
+            }
+            let settings: Vec<String> = settings
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect();
+            config = ron::from_str(&format!("({})", settings.join(", ")))
+                .map_err(|err| invalid(format!("overrides: {}", err)))?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        let positive = [
+            ("player_speed", self.player_speed),
+            ("lazer_speed", self.lazer_speed),
+            ("alien_bullet_speed", self.alien_bullet_speed),
+            ("missile_speed", self.missile_speed),
+            ("missile_lifetime", self.missile_lifetime),
+            ("mystery_ship_speed", self.mystery_ship_speed),
+            ("state_transition_menu", self.state_transition_menu),
+            ("state_transition_start", self.state_transition_start),
+            ("state_transition_new_wave", self.state_transition_new_wave),
+            ("state_transition_spawn", self.state_transition_spawn),
+            (
+                "state_transition_name_entry",
+                self.state_transition_name_entry,
+            ),
+            ("score_scale", self.score_scale),
+            ("power_up_duration", self.power_up_duration),
+        ];
+        for (name, value) in positive {
+            if value.is_nan() || value <= 0.0 {
+                return Err(invalid(format!("{} should be positive", name)));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.player_slow) {
+            return Err(invalid("player_slow should be within 0..=1"));
+        }
+        if !(0.0..=1.0).contains(&self.missile_probability) {
+            return Err(invalid("missile_probability should be within 0..=1"));
+        }
+        if !(0.0..=1.0).contains(&self.power_up_probability) {
+            return Err(invalid("power_up_probability should be within 0..=1"));
+        }
+        if self.missile_interval < 0.0 || self.missile_turn_rate < 0.0 {
+            return Err(invalid(
+                "missile_interval and missile_turn_rate should not be negative",
+            ));
+        }
+        if self.player_spawn_counter == 0 {
+            return Err(invalid("player_spawn_counter should be at least 1"));
+        }
+        if self.max_lazers == 0 {
+            return Err(invalid("max_lazers should be at least 1"));
+        }
+        if self.nr_lives == 0 {
+            return Err(invalid("nr_lives should be at least 1"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Difficulty presets and adaptive difficulty
+//!
+//! The preset scales the alien speed, the alien fire rate and bullet speed of
+//! each wave, and the extra life threshold. With adaptive difficulty, the
+//! pressure is adjusted at a regular interval during play, raised while the
+//! player is accurate, and lowered on poor accuracy or a life lost.
+
+use crate::{
+    common::*,
+    config::GameConfig,
+    game_state::{GameState, Store},
+    wave::enter_wave_system,
+    SpaceSet,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AdaptiveDifficulty>()
+            .add_systems(
+                OnEnter(GameState::Start),
+                (start_system, enter_wave_difficulty_system)
+                    .chain()
+                    .after(enter_wave_system),
+            )
+            .add_systems(
+                OnEnter(GameState::NewWave),
+                enter_wave_difficulty_system.after(enter_wave_system),
+            )
+            .add_systems(
+                FixedUpdate,
+                adaptive_system
+                    .in_set(SpaceSet::Logic)
+                    .run_if(in_state(GameState::Play)),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// as the arcade original, the first extra life at 1500 points
+    Arcade,
+}
+
+/// Factors applied to the `Store` by a difficulty
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DifficultyScale {
+    pub alien_speed: f32,
+    pub bullet_interval: f32,
+    pub bullet_speed: f32,
+    pub score_new_life: f32,
+}
+
+impl Difficulty {
+    pub fn scale(&self) -> DifficultyScale {
+        let (alien_speed, bullet_interval, bullet_speed, score_new_life) = match self {
+            Difficulty::Easy => (0.8, 1.5, 0.75, 0.5),
+            Difficulty::Normal => (1.0, 1.0, 1.0, 1.0),
+            Difficulty::Hard => (1.25, 0.67, 1.25, 2.0),
+            Difficulty::Arcade => (1.0, 1.0, 1.0, 15.0),
+        };
+        DifficultyScale {
+            alien_speed,
+            bullet_interval,
+            bullet_speed,
+            score_new_life,
+        }
+    }
+}
+
+/// Adaptive difficulty, the pressure level and the play since the last adjustment
+#[derive(Resource)]
+pub struct AdaptiveDifficulty {
+    level: f32,
+    timer: Timer,
+    shots_fired: u32,
+    shots_hit: u32,
+    lives: u8,
+}
+
+impl Default for AdaptiveDifficulty {
+    fn default() -> Self {
+        AdaptiveDifficulty {
+            level: 1.0,
+            timer: Timer::from_seconds(ADAPTIVE_INTERVAL, TimerMode::Repeating),
+            shots_fired: 0,
+            shots_hit: 0,
+            lives: 0,
+        }
+    }
+}
+
+impl AdaptiveDifficulty {
+    /// Pressure, scaling the alien speed and fire rate (1.0 as the preset)
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+// a new game, the store is reset
+pub fn start_system(
+    config: Res<GameConfig>,
+    mut store: ResMut<Store>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+) {
+    let nr_players = store.nr_players;
+    for player in &mut store.players[..nr_players] {
+        player.score_new_life =
+            (config.score_new_life as f32 * config.difficulty.scale().score_new_life) as u32;
+    }
+    *adaptive = AdaptiveDifficulty {
+        lives: store.lives,
+        ..default()
+    };
+}
+
+// on entering Start and NewWave, once the wave has set up the store
+pub fn enter_wave_difficulty_system(
+    config: Res<GameConfig>,
+    mut store: ResMut<Store>,
+    adaptive: Res<AdaptiveDifficulty>,
+) {
+    let scale = config.difficulty.scale();
+    debug!(
+        "difficulty {:?}, level {}",
+        config.difficulty, adaptive.level
+    );
+    store.alien_speed *= scale.alien_speed * adaptive.level;
+    store.bullet_interval *= scale.bullet_interval / adaptive.level;
+    store.bullet_speed *= scale.bullet_speed;
+}
+
+pub fn adaptive_system(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut store: ResMut<Store>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+) {
+    if !config.adaptive_difficulty {
+        return;
+    }
+    adaptive.timer.tick(time.delta());
+    if !adaptive.timer.just_finished() {
+        return;
+    }
+
+    let shots = store.shots_fired - adaptive.shots_fired;
+    let accuracy = (store.shots_hit - adaptive.shots_hit) as f32 / shots.max(1) as f32;
+    let life_lost = store.lives < adaptive.lives;
+    let mut level = adaptive.level;
+    if life_lost || shots >= ADAPTIVE_MIN_SHOTS && accuracy < ADAPTIVE_ACCURACY_LOW {
+        level -= ADAPTIVE_STEP;
+    } else if shots >= ADAPTIVE_MIN_SHOTS && accuracy > ADAPTIVE_ACCURACY_HIGH {
+        level += ADAPTIVE_STEP;
+    }
+    let level = level.clamp(ADAPTIVE_MIN, ADAPTIVE_MAX);
+
+    if level != adaptive.level {
+        debug!(
+            "adaptive difficulty, accuracy {}, life lost {}, level {}",
+            accuracy, life_lost, level
+        );
+        store.alien_speed *= level / adaptive.level;
+        store.bullet_interval *= adaptive.level / level;
+        adaptive.level = level;
+    }
+    adaptive.shots_fired = store.shots_fired;
+    adaptive.shots_hit = store.shots_hit;
+    adaptive.lives = store.lives;
+}
@@ -1,34 +1,103 @@
 use crate::{
-    alien,
-    audio::PlayMusicEvent,
-    bunker::{self, Bunker},
+    audio::{PlayMusicEvent, PlaySoundEvent},
     common::*,
+    config::GameConfig,
+    leader_board::LeaderBoard,
+    wave::Wave,
+    SpaceSet,
 };
 use bevy::prelude::*;
 use std::{default::Default, time::Duration};
 
-#[derive(PartialEq, Debug)]
+/// Game state and store
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        // resources are inserted up front, as the initial OnEnter runs before Startup
+        app.init_resource::<Store>()
+            .init_resource::<SpawnCounter>()
+            .init_resource::<TimerResource>()
+            .init_state::<GameState>()
+            .add_event::<GameStateEvent>()
+            .add_event::<PlayMusicEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(
+                FixedUpdate,
+                (extra_life_system, update_system)
+                    .chain()
+                    .in_set(SpaceSet::Logic),
+            )
+            .add_systems(
+                FixedUpdate,
+                game_state_event_system.in_set(SpaceSet::Events),
+            )
+            .add_systems(OnEnter(GameState::GameOver), enter_menu_system)
+            .add_systems(OnEnter(GameState::InsertCoin), enter_menu_system)
+            .add_systems(OnEnter(GameState::LeaderBoard), enter_menu_system)
+            .add_systems(OnEnter(GameState::Start), enter_start_system)
+            .add_systems(OnEnter(GameState::NewWave), enter_new_wave_system)
+            .add_systems(OnEnter(GameState::PlayerSpawn), enter_player_spawn_system)
+            .add_systems(OnEnter(GameState::Play), enter_play_system)
+            .add_systems(OnEnter(GameState::Turn), enter_turn_system)
+            .add_systems(OnEnter(GameState::EnterName), enter_name_system);
+    }
+}
+
+#[derive(States, Default, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum GameState {
     GameOver,
+    #[default]
     InsertCoin,
     LeaderBoard,
     Start,
-    PlayerSpawn(u8),
+    PlayerSpawn,
     Play,
     NewWave,
+    EnterName,
+    /// alternating two-player, the other player's turn
+    Turn,
+}
+
+/// Score and lives of a player
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerStore {
+    pub score: u32,
+    pub lives: u8,
+    pub score_new_life: u32,
+}
+
+impl Default for PlayerStore {
+    fn default() -> Self {
+        PlayerStore {
+            score: 0,
+            lives: 0,
+            score_new_life: 100,
+        }
+    }
 }
 
 #[derive(Resource)]
 pub struct Store {
+    /// score and lives of all players together, as the single player's
     pub score: u32,
-    pub score_new_life: u32,
+    pub lives: u8,
+    pub players: [PlayerStore; MAX_PLAYERS],
+    /// players in the game, two in co-op and alternating
+    pub nr_players: usize,
+    /// the players take turns, one ship on screen
+    pub alternating: bool,
+    /// the player playing, in alternating
+    pub turn: usize,
     pub bullet_interval: f32,
+    pub bullet_speed: f32,
     pub aliens_killed: u8,
+    pub shots_fired: u32,
+    /// shots hitting an alien, missile or the mystery ship
+    pub shots_hit: u32,
     pub alien_speed: f32,
     pub wave: u8,
-    pub lives: u8,
     pub player_count_down: f32,
-    pub game_state: GameState,
     pub show_state: bool,
 }
 
@@ -36,14 +105,19 @@ impl Default for Store {
     fn default() -> Self {
         Store {
             score: 0,
-            score_new_life: 100,
+            lives: 0,
+            players: [PlayerStore::default(); MAX_PLAYERS],
+            nr_players: 1,
+            alternating: false,
+            turn: 0,
             bullet_interval: ALIEN_BULLET_INTERVAL,
+            bullet_speed: GameConfig::default().alien_bullet_speed,
             aliens_killed: 0,
+            shots_fired: 0,
+            shots_hit: 0,
             alien_speed: ALIENS_SPEED_START,
             wave: 1,
-            lives: 0,
             player_count_down: 3.0,
-            game_state: GameState::InsertCoin,
             show_state: false,
         }
     }
@@ -53,17 +127,55 @@ impl Store {
     pub fn reset(&mut self) {
         *self = Self { ..default() }
     }
-}
 
-// Store resource and StateTransitionTimer
-pub fn setup(mut commands: Commands) {
-    commands.insert_resource(Store { ..default() });
-    commands.insert_resource(TimerResource(Timer::from_seconds(
-        STATE_TRANSITION_MENU,
-        TimerMode::Repeating,
-    )));
+    /// A new game for `nr_players`, each with `lives`, the display kept as is
+    pub fn start(&mut self, nr_players: usize, lives: u8, score_new_life: u32) {
+        let show_state = self.show_state;
+        self.reset();
+        self.show_state = show_state;
+        self.nr_players = nr_players;
+        for player in &mut self.players[..nr_players] {
+            player.lives = lives;
+            player.score_new_life = score_new_life;
+        }
+        self.lives = lives * nr_players as u8;
+    }
+
+    /// Ships on screen, a single one taking turns
+    pub fn ships(&self) -> usize {
+        if self.alternating {
+            1
+        } else {
+            self.nr_players
+        }
+    }
+
+    pub fn add_score(&mut self, player: usize, score: u32) {
+        self.score += score;
+        self.players[player].score += score;
+    }
+
+    /// The event starting a new game of the same mode
+    pub fn press_play(&self) -> GameStateEvent {
+        if self.alternating {
+            GameStateEvent::PressPlayAlternating
+        } else if self.nr_players > 1 {
+            GameStateEvent::PressPlayCoOp
+        } else {
+            GameStateEvent::PressPlay
+        }
+    }
+
+    /// The aliens of the wave not killed yet
+    pub fn aliens_left(&self, wave: &Wave) -> u8 {
+        wave.aliens_total().saturating_sub(self.aliens_killed)
+    }
 }
 
+/// Remaining blinks before the player is spawned, counts down in `PlayerSpawn`
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SpawnCounter(pub u8);
+
 pub fn cleanup_state<T>(commands: &mut Commands, query: Query<Entity, With<T>>)
 where
     T: Component,
@@ -73,9 +185,16 @@ where
     }
 }
 
+/// The state transition timer, set on entering each state
 #[derive(Resource, Deref, DerefMut)]
 pub struct TimerResource(Timer);
 
+impl Default for TimerResource {
+    fn default() -> Self {
+        TimerResource(Timer::new(Duration::ZERO, TimerMode::Repeating))
+    }
+}
+
 impl TimerResource {
     pub fn set(&mut self, duration: f32) {
         self.0.set_duration(Duration::from_secs_f32(duration));
@@ -87,7 +206,12 @@ impl TimerResource {
 #[derive(Event, Debug)]
 pub enum GameStateEvent {
     PressPlay,
-    LooseLife,
+    /// two players at the same time
+    PressPlayCoOp,
+    /// two players taking turns
+    PressPlayAlternating,
+    /// the player hit
+    LooseLife(usize),
     NewWave,
     Info,
 }
@@ -95,39 +219,49 @@ pub enum GameStateEvent {
 pub fn game_state_event_system(
     mut game_state_er: EventReader<GameStateEvent>,
     mut play_music_event_writer: EventWriter<PlayMusicEvent>,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
     mut store: ResMut<Store>,
-    mut timer: ResMut<TimerResource>,
+    config: Res<GameConfig>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     for event in game_state_er.read() {
         debug!("game state event received : {:?}", event);
         match event {
-            GameStateEvent::PressPlay => {
+            GameStateEvent::PressPlay
+            | GameStateEvent::PressPlayCoOp
+            | GameStateEvent::PressPlayAlternating => {
                 debug!("press play received");
                 play_music_event_writer.send(PlayMusicEvent(false));
-                store.reset();
-                store.lives = NR_LIVES;
-                store.game_state = GameState::Start;
-                timer.set(STATE_TRANSITION_START);
+                play_sound_ew.send(PlaySoundEvent::InsertCoin);
+                let nr_players = match event {
+                    GameStateEvent::PressPlay => 1,
+                    _ => 2,
+                };
+                store.start(nr_players, config.nr_lives, config.score_new_life);
+                store.alternating = matches!(event, GameStateEvent::PressPlayAlternating);
+                next_state.set(GameState::Start);
             }
-            GameStateEvent::LooseLife => {
-                if store.game_state == GameState::Play {
-                    store.lives -= 1;
+            GameStateEvent::LooseLife(hit) => {
+                if *state.get() == GameState::Play {
+                    let player = &mut store.players[*hit];
+                    player.lives = player.lives.saturating_sub(1);
+                    let respawn = player.lives > 0;
+                    store.lives = store.lives.saturating_sub(1);
+                    let other = (*hit + 1) % store.nr_players;
                     if store.lives == 0 {
-                        store.game_state = GameState::GameOver;
-                        timer.set(STATE_TRANSITION_MENU);
-                    } else {
-                        store.game_state = GameState::PlayerSpawn(PLAYER_SPAWN_COUNTER);
-                        timer.set(STATE_TRANSITION_SPAWN);
+                        next_state.set(GameState::GameOver);
+                    } else if store.alternating && store.players[other].lives > 0 {
+                        next_state.set(GameState::Turn);
+                    } else if respawn {
+                        next_state.set(GameState::PlayerSpawn);
                     }
+                    // else, out of lives, the other player plays on
                 }
             }
             GameStateEvent::NewWave => {
-                store.game_state = GameState::NewWave;
-                store.aliens_killed = 0;
-                store.alien_speed = ALIENS_SPEED_START + store.wave as f32 * ALIENS_SPEED_WAVE;
-                store.wave += 1;
-                store.bullet_interval *= BULLET_INTERVAL_WAVE;
-                timer.set(STATE_TRANSITION_NEW_WAVE);
+                play_sound_ew.send(PlaySoundEvent::WaveClear);
+                next_state.set(GameState::NewWave);
             }
             GameStateEvent::Info => {
                 debug!("info received");
@@ -137,70 +271,108 @@ pub fn game_state_event_system(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn update_system(
-    mut commands: Commands,
-    time: Res<Time>,
+/// GameOver, InsertCoin and LeaderBoard cycle on the menu timer
+pub fn enter_menu_system(config: Res<GameConfig>, mut timer: ResMut<TimerResource>) {
+    timer.set(config.state_transition_menu);
+}
+
+pub fn enter_start_system(config: Res<GameConfig>, mut timer: ResMut<TimerResource>) {
+    debug!("--- Start ---");
+    timer.set(config.state_transition_start);
+}
+
+pub fn enter_new_wave_system(
     mut store: ResMut<Store>,
+    config: Res<GameConfig>,
     mut timer: ResMut<TimerResource>,
+) {
+    debug!("--- New Wave ---");
+    store.aliens_killed = 0;
+    store.wave += 1;
+    timer.set(config.state_transition_new_wave);
+}
 
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
-    alien_query: Query<Entity, With<alien::Alien>>,
-    alien_bullet_query: Query<Entity, With<alien::AlienBullet>>,
-    bunker_query: Query<Entity, With<Bunker>>,
+pub fn enter_player_spawn_system(
+    config: Res<GameConfig>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    mut timer: ResMut<TimerResource>,
 ) {
-    timer.tick(time.delta());
+    debug!("--- Player Spawn ---");
+    **spawn_counter = config.player_spawn_counter;
+    timer.set(config.state_transition_spawn);
+}
+
+// the turn is announced as the start of a game
+pub fn enter_turn_system(config: Res<GameConfig>, mut timer: ResMut<TimerResource>) {
+    debug!("--- Turn ---");
+    timer.set(config.state_transition_start);
+}
 
-    // extra life(s)
-    if store.score >= store.score_new_life {
-        store.lives += 1;
-        store.score_new_life += (store.score_new_life as f32 * SCORE_SCALE) as u32;
+pub fn enter_play_system(mut timer: ResMut<TimerResource>) {
+    debug!("--- Play, pause timer ---");
+    timer.pause();
+}
+
+pub fn enter_name_system(config: Res<GameConfig>, mut timer: ResMut<TimerResource>) {
+    debug!("--- Enter Name ---");
+    timer.set(config.state_transition_name_entry);
+}
+
+// extra life(s), for each player by its own score
+pub fn extra_life_system(
+    config: Res<GameConfig>,
+    mut store: ResMut<Store>,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+) {
+    let nr_players = store.nr_players;
+    let mut extra_lives = 0;
+    for player in &mut store.players[..nr_players] {
+        // a player out of lives is out of the game
+        if player.lives > 0 && player.score >= player.score_new_life {
+            player.lives += 1;
+            player.score_new_life += (player.score_new_life as f32 * config.score_scale) as u32;
+            extra_lives += 1;
+            play_sound_ew.send(PlaySoundEvent::ExtraLife);
+        }
     }
+    store.lives += extra_lives;
+}
+
+pub fn update_system(
+    time: Res<Time>,
+    store: Res<Store>,
+    mut timer: ResMut<TimerResource>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    // the leader board plugin may be left out
+    leader_board: Option<Res<LeaderBoard>>,
+) {
+    timer.tick(time.delta());
 
-    // state transition
+    // state transition, the timer is paused during Play
     if timer.just_finished() {
-        store.game_state = match store.game_state {
-            GameState::GameOver => GameState::InsertCoin,
-            GameState::InsertCoin => GameState::LeaderBoard,
-            GameState::LeaderBoard => GameState::InsertCoin,
-            GameState::Start | GameState::NewWave => {
-                alien::reset(
-                    &mut commands,
-                    &asset_server,
-                    &mut texture_atlas_layout,
-                    alien_query,
-                    alien_bullet_query,
-                );
-                bunker::reset(
-                    &mut commands,
-                    &asset_server,
-                    &mut texture_atlas_layout,
-                    bunker_query,
-                );
-
-                if store.game_state == GameState::Start {
-                    debug!("--- Start ---");
+        match state.get() {
+            GameState::GameOver => {
+                if leader_board.is_some_and(|board| board.qualifying_player(&store, 0).is_some()) {
+                    next_state.set(GameState::EnterName)
                 } else {
-                    debug!("--- New Wave ---");
+                    next_state.set(GameState::InsertCoin)
                 }
-                timer.set(STATE_TRANSITION_SPAWN);
-                GameState::PlayerSpawn(PLAYER_SPAWN_COUNTER)
             }
-            GameState::PlayerSpawn(ref mut p) => {
-                debug!("--- Player Spawn ---");
-                *p -= 1;
-                if *p == 0 {
-                    debug!("--- Play, pause timer ---");
-                    timer.pause();
-                    GameState::Play
-                } else {
-                    GameState::PlayerSpawn(*p)
-                }
+            GameState::InsertCoin => next_state.set(GameState::LeaderBoard),
+            GameState::LeaderBoard => next_state.set(GameState::InsertCoin),
+            GameState::Start | GameState::NewWave | GameState::Turn => {
+                next_state.set(GameState::PlayerSpawn)
             }
-            GameState::Play => {
-                panic!("timer should be paused, thus unreachable");
+            GameState::EnterName => next_state.set(GameState::LeaderBoard),
+            GameState::PlayerSpawn => {
+                **spawn_counter -= 1;
+                if **spawn_counter == 0 {
+                    next_state.set(GameState::Play);
+                }
             }
-        };
+            GameState::Play => {}
+        }
     }
 }
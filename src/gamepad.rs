@@ -1,34 +1,202 @@
-use crate::{common::*, game_state::*, lazer::FireLazerEvent, player::PlayerEvent};
-use bevy::prelude::*;
+//! Gamepad input, each gamepad playing the player it joined as
+//!
+//! A gamepad joins by pressing Fire (South, unless bound otherwise), as the first
+//! player without a gamepad. Disconnecting the gamepad of a player on screen opens
+//! the pause menu, resumed when a gamepad joins in its place. The controller of each ship is shown below it.
 
+use crate::{
+    action::{self, Action, ActionEvent, Bindings},
+    common::*,
+    game_state::*,
+    pause::PauseState,
+    player::{self, Player},
+    SpaceSet,
+};
+use bevy::{input::gamepad::GamepadConnection, input::gamepad::GamepadConnectionEvent, prelude::*};
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GamepadAssignment>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (connection_system, update_system)
+                    .chain()
+                    .in_set(SpaceSet::Input)
+                    .before(action::update_system),
+            )
+            .add_systems(Update, label_system.in_set(SpaceSet::Logic));
+    }
+}
+
+/// The gamepad of each player, a gamepad joins by pressing Fire
+#[derive(Resource, Default)]
+pub struct GamepadAssignment {
+    players: [Option<Gamepad>; MAX_PLAYERS],
+    /// the player whose gamepad was disconnected in game, paused until one joins
+    paused: Option<usize>,
+}
+
+impl GamepadAssignment {
+    /// The player of the gamepad, if joined
+    pub fn player(&self, gamepad: Gamepad) -> Option<usize> {
+        self.players.iter().position(|pad| *pad == Some(gamepad))
+    }
+
+    /// The gamepad of the player, if any
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.players[player]
+    }
+
+    /// Joins the gamepad as the first player without one, if any left
+    pub fn join(&mut self, gamepad: Gamepad) -> Option<usize> {
+        if let Some(player) = self.player(gamepad) {
+            return Some(player);
+        }
+        let player = self.players.iter().position(Option::is_none)?;
+        self.players[player] = Some(gamepad);
+        Some(player)
+    }
+
+    /// The gamepad leaves, the player it played if any
+    pub fn leave(&mut self, gamepad: Gamepad) -> Option<usize> {
+        let player = self.player(gamepad)?;
+        self.players[player] = None;
+        Some(player)
+    }
+
+    /// The player whose gamepad was disconnected during the game, if paused
+    pub fn paused(&self) -> Option<usize> {
+        self.paused
+    }
+
+    /// The player of the keyboard, in co-op the first player without a gamepad
+    pub fn keyboard_player(&self, store: &Store) -> usize {
+        if store.ships() > 1 {
+            (0..store.nr_players)
+                .find(|player| self.players[*player].is_none())
+                .unwrap_or(0)
+        } else {
+            store.turn
+        }
+    }
+}
+
+/// The label below the ship of a player, showing its controller
+#[derive(Component)]
+pub struct ControllerLabel(pub usize);
+
+pub fn setup(mut commands: Commands) {
+    for player in 0..MAX_PLAYERS {
+        commands.spawn((
+            ControllerLabel(player),
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: CONTROLLER_LABEL_FONT_SIZE,
+                        color: player::color(player),
+                        ..default()
+                    },
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+}
+
+// a gamepad disconnected in game pauses the game, until a gamepad joins in its place,
+// only for the player on screen, the one taking its turn when alternating
+pub fn connection_system(
+    mut connection_er: EventReader<GamepadConnectionEvent>,
+    mut assignment: ResMut<GamepadAssignment>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    bindings: Res<Bindings>,
+    store: Res<Store>,
+    state: Res<State<GameState>>,
+) {
+    for event in connection_er.read() {
+        match event.connection {
+            GamepadConnection::Connected(_) => {
+                let fire: Vec<String> = bindings
+                    .buttons(Action::Fire)
+                    .iter()
+                    .map(|button| format!("{:?}", button))
+                    .collect();
+                info!(
+                    "{:?} connected, press {} to join",
+                    event.gamepad,
+                    fire.join(" or ")
+                );
+            }
+            GamepadConnection::Disconnected => {
+                let Some(player) = assignment.leave(event.gamepad) else {
+                    continue;
+                };
+                info!("{:?} of player {} disconnected", event.gamepad, player);
+                let on_screen = if store.alternating {
+                    player == store.turn
+                } else {
+                    player < store.nr_players && store.players[player].lives > 0
+                };
+                if matches!(state.get(), GameState::PlayerSpawn | GameState::Play) && on_screen {
+                    assignment.paused = Some(player);
+                    next_pause_state.set(PauseState::Paused);
+                }
+            }
+        }
+    }
+}
+
+/// gamepad actions of the joined gamepads, each playing its player in co-op,
+/// otherwise playing the single ship
+#[allow(clippy::too_many_arguments)]
 pub fn update_system(
-    mut fire_lazer_ew: EventWriter<FireLazerEvent>,
-    mut game_state_ew: EventWriter<GameStateEvent>,
-    mut player_ew: EventWriter<PlayerEvent>,
+    mut action_ew: EventWriter<ActionEvent>,
+    mut assignment: ResMut<GamepadAssignment>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    bindings: Res<Bindings>,
+    store: Res<Store>,
 
     gamepads: Res<Gamepads>,
     button_inputs: Res<ButtonInput<GamepadButton>>,
 
     axes: Res<Axis<GamepadAxis>>,
-    store: ResMut<Store>,
 ) {
     for gamepad in gamepads.iter() {
-        if button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)) {
-            trace!("{:?} just pressed South", gamepad);
-            match store.game_state {
-                GameState::InsertCoin | GameState::LeaderBoard => {
-                    game_state_ew.send(GameStateEvent::PressPlay);
+        let buttons = |action| {
+            bindings
+                .buttons(action)
+                .iter()
+                .map(move |button_type| GamepadButton::new(gamepad, *button_type))
+        };
+        let Some(owner) = assignment.player(gamepad) else {
+            // joining by Fire
+            if button_inputs.any_just_pressed(buttons(Action::Fire)) {
+                if let Some(player) = assignment.join(gamepad) {
+                    info!("{:?} joined as player {}", gamepad, player);
+                    if assignment.paused == Some(player) {
+                        assignment.paused = None;
+                        if *pause_state.get() == PauseState::Paused {
+                            next_pause_state.set(PauseState::Running);
+                        }
+                    }
                 }
-                GameState::PlayerSpawn(_) | GameState::Play => {
-                    fire_lazer_ew.send(FireLazerEvent);
-                }
-                _ => {}
             }
-        }
+            continue;
+        };
+        let player = if store.ships() > 1 { owner } else { store.turn };
 
-        if button_inputs.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::North)) {
-            trace!("{:?} just pressed North", gamepad);
-            game_state_ew.send(GameStateEvent::Info);
+        for action in Action::ALL {
+            if button_inputs.any_pressed(buttons(action)) {
+                let just_pressed = button_inputs.any_just_pressed(buttons(action));
+                trace!("{:?} {:?}", gamepad, action);
+                action_ew.send(ActionEvent::button(player, action, just_pressed));
+            }
         }
 
         let left_stick_x = axes
@@ -38,7 +206,48 @@ pub fn update_system(
         // hysteresis set at 0.01 to avoid drift
         if left_stick_x.abs() > LEFT_STICK_HYSTERESIS {
             trace!("{:?} LeftStickX value is {}", gamepad, left_stick_x);
-            player_ew.send(PlayerEvent(left_stick_x));
+            let action = if left_stick_x < 0.0 {
+                Action::MoveLeft
+            } else {
+                Action::MoveRight
+            };
+            action_ew.send(ActionEvent {
+                player,
+                action,
+                value: left_stick_x.abs(),
+                just_pressed: false,
+            });
         }
     }
 }
+
+// the controller of each ship, shown below it
+#[allow(clippy::type_complexity)]
+pub fn label_system(
+    assignment: Res<GamepadAssignment>,
+    store: Res<Store>,
+    player_query: Query<(&Player, &Transform)>,
+    mut label_query: Query<
+        (&ControllerLabel, &mut Text, &mut Transform, &mut Visibility),
+        Without<Player>,
+    >,
+) {
+    for (label, mut text, mut transform, mut visibility) in &mut label_query {
+        let ship = player_query
+            .iter()
+            .find(|(player, _)| player.0 == label.0)
+            .map(|(_, transform)| transform.translation);
+        let controller = match assignment.gamepad(label.0) {
+            Some(gamepad) => format!("PAD {}", gamepad.id + 1),
+            None if assignment.keyboard_player(&store) == label.0 => "KEYS".to_string(),
+            None => "-".to_string(),
+        };
+        let Some(ship) = ship else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        text.sections[0].value = format!("P{} {}", label.0 + 1, controller);
+        transform.translation = ship - Vec3::Y * CONTROLLER_LABEL_OFFSET;
+        *visibility = Visibility::Visible;
+    }
+}
@@ -0,0 +1,62 @@
+//! Headless simulation, without window, rendering, audio or input devices
+//!
+//! Time advances by exactly one simulation tick for each `App::update`,
+//! making the simulation independent of the wall clock. Input is provided by sending
+//! the `PlayerEvent`, `FireLazerEvent` and `GameStateEvent` events directly, or the
+//! `ActionEvent`s as if from the keyboard or a gamepad.
+
+use crate::{
+    action::ActionPlugin,
+    audio, gamepad, keyboard_input,
+    leader_board::LeaderBoardPlugin,
+    overlay,
+    replay::{ReplayMode, ReplayPlugin},
+    rng::RngPlugin,
+    settings::SettingsPlugin,
+    simulation::{InterpolationPlugin, SimulationPlugin},
+    wave::WavePlugin,
+    SpaceInvadersPlugin,
+};
+use bevy::{asset::AssetPlugin, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+use std::time::Duration;
+
+/// Simulation ticks per second
+pub const HEADLESS_TICK_RATE: f64 = 60.0;
+
+/// Builds a headless game `App`, running one tick at `tick_rate` per update
+pub fn app(tick_rate: f64) -> App {
+    app_with_seed(tick_rate, 0)
+}
+
+/// Builds a headless game `App` with the given rng seed
+pub fn app_with_seed(tick_rate: f64, seed: u64) -> App {
+    app_with_replay(tick_rate, seed, ReplayMode::Off)
+}
+
+/// Builds a headless game `App` recording or playing back a replay
+pub fn app_with_replay(tick_rate: f64, seed: u64, mode: ReplayMode) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+        // assets are handles only, no loaders are registered
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / tick_rate,
+        )))
+        .add_plugins(
+            SpaceInvadersPlugin
+                .set(SimulationPlugin { tick_rate })
+                .set(RngPlugin { seed: Some(seed) })
+                .set(ReplayPlugin { mode })
+                .set(LeaderBoardPlugin { path: None })
+                .set(WavePlugin { path: None })
+                .set(ActionPlugin { path: None })
+                .set(SettingsPlugin { path: None })
+                .disable::<InterpolationPlugin>()
+                .disable::<overlay::OverlayPlugin>()
+                .disable::<audio::AudioPlugin>()
+                .disable::<keyboard_input::KeyboardInputPlugin>()
+                .disable::<gamepad::GamepadPlugin>(),
+        );
+    app
+}
@@ -3,27 +3,48 @@ use crate::{
     audio::*,
     bunker::*,
     common::*,
-    // game_state::{GameState, StateTransitionTimer, Store},
+    config::GameConfig,
     game_state::{GameState, GameStateEvent, Store},
-    lazer::Lazer,
+    lazer::{Lazer, LazerShot},
+    mystery_ship::{self, MysteryShip},
     particle::*,
     player::Player,
+    wave::CurrentWave,
+    SpaceSet,
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+
+pub struct HitDetectionPlugin;
+
+impl Plugin for HitDetectionPlugin {
+    fn build(&self, app: &mut App) {
+        // the events are also registered here, so the audio plugin can be left out
+        app.add_event::<PlaySoundEvent>()
+            .add_event::<GameStateEvent>()
+            .add_event::<AlienKilledEvent>()
+            .add_systems(FixedUpdate, update_system.in_set(SpaceSet::Collision));
+    }
+}
 
 #[allow(clippy::too_many_arguments)]
 pub fn update_system(
     mut commands: Commands,
     mut store: ResMut<Store>,
+    state: Res<State<GameState>>,
+    config: Res<GameConfig>,
+    current_wave: Res<CurrentWave>,
     image: Res<CrossImage>,
     mut game_state_ew: EventWriter<GameStateEvent>,
     mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    mut alien_killed_ew: EventWriter<AlienKilledEvent>,
 
-    alien_query: Query<(Entity, &Transform), With<Alien>>,
-    mut lazer_query: Query<(&mut Lazer, &Transform)>,
+    mut alien_query: Query<(Entity, &Transform, &AlienKind, &mut HitPoints), With<Alien>>,
+    mystery_ship_query: Query<(Entity, &Transform), With<MysteryShip>>,
+    lazer_query: Query<(Entity, &Lazer, &LazerShot, &Transform)>,
     mut bunker_query: Query<(&mut TextureAtlas, Entity, &Transform), With<Bunker>>,
     alien_bullet_query: Query<(Entity, &Transform), With<AlienBullet>>,
-    mut player_query: Query<&Transform, With<Player>>,
+    missile_query: Query<(Entity, &Transform), With<HomingMissile>>,
+    player_query: Query<(&Player, &Transform)>,
 ) {
     // check if point:&Transform is in &target:Transform with size:Vec2
     #[inline(always)]
@@ -34,40 +55,60 @@ pub fn update_system(
         rect.contains(p_vec)
     }
 
-    let commands = &mut commands;
+    // a lazer is spent on its first hit
+    fn spend(commands: &mut Commands, destroyed: &mut HashSet<Entity>, lazer: Entity) {
+        if destroyed.insert(lazer) {
+            commands.entity(lazer).despawn();
+        }
+    }
 
-    // get lazer singleton
-    let (mut lazer, lazer_transform) = lazer_query.get_single_mut().unwrap();
+    let commands = &mut commands;
+    // entities hit this tick
+    let mut destroyed = HashSet::new();
 
-    // get a player singleton
-    let player_transform = player_query.single_mut();
+    // the player hit at point, if any
+    let player_hit = |point: &Transform| {
+        player_query
+            .iter()
+            .find(|(_, player_transform)| in_rect(point, player_transform, PLAYER_SIZE))
+            .map(|(player, _)| player.0)
+    };
     // alien bullets
-    for (bullet_entity, bullet_transform) in &alien_bullet_query {
+    'bullets: for (bullet_entity, bullet_transform) in &alien_bullet_query {
         // hit player missile
-        if in_rect(bullet_transform, lazer_transform, (16.0, 32.0).into()) {
-            commands.entity(bullet_entity).despawn();
-            *lazer = Lazer::Idle;
-            spawn_explosion(
-                commands,
-                &image,
-                10,
-                (
-                    bullet_transform.translation.x,
-                    bullet_transform.translation.y,
-                )
-                    .into(),
-                150.0,
-                0.0,
-                (10.0, 10.0).into(),
-            );
-        } else
+        for (lazer_entity, _, _, lazer_transform) in &lazer_query {
+            if !destroyed.contains(&lazer_entity)
+                && in_rect(bullet_transform, lazer_transform, (16.0, 32.0).into())
+            {
+                commands.entity(bullet_entity).despawn();
+                spend(commands, &mut destroyed, lazer_entity);
+                spawn_explosion(
+                    commands,
+                    &image,
+                    10,
+                    (
+                        bullet_transform.translation.x,
+                        bullet_transform.translation.y,
+                    )
+                        .into(),
+                    150.0,
+                    0.0,
+                    (10.0, 10.0).into(),
+                );
+                continue 'bullets;
+            }
+        }
         // hit player
-        if in_rect(bullet_transform, player_transform, PLAYER_SIZE) {
+        if let Some(hit) = player_hit(bullet_transform) {
             commands.entity(bullet_entity).despawn();
-            game_state_ew.send(GameStateEvent::LooseLife);
+            game_state_ew.send(GameStateEvent::LooseLife(hit));
+            play_sound_ew.send(PlaySoundEvent::PlayerDeath);
             // to prevent the rare race-condition when outstanding missile would cause an extra life
-
-            *lazer = Lazer::Idle;
+            for (lazer_entity, lazer, ..) in &lazer_query {
+                if lazer.player == hit {
+                    spend(commands, &mut destroyed, lazer_entity);
+                }
+            }
 
             spawn_explosion(
                 commands,
@@ -87,8 +128,9 @@ pub fn update_system(
             for (bunker_atlas, bunker_entity, bunker_transform) in &mut bunker_query {
                 if in_rect(bullet_transform, bunker_transform, BUNKER_SIZE) {
                     commands.entity(bullet_entity).despawn();
-                    if store.game_state == GameState::Play {
+                    if *state.get() == GameState::Play && destroyed.insert(bunker_entity) {
                         hit_bunker(commands, bunker_entity, bunker_atlas);
+                        play_sound_ew.send(PlaySoundEvent::BunkerHit);
                     }
                     spawn_explosion(
                         commands,
@@ -108,49 +150,181 @@ pub fn update_system(
         }
     }
 
-    if let Lazer::Fired(_) = *lazer {
+    // homing missiles
+    'missiles: for (missile_entity, missile_transform) in &missile_query {
+        let position = missile_transform.translation.truncate();
+        // shot down by player missile
+        for (lazer_entity, lazer, _, lazer_transform) in &lazer_query {
+            if !destroyed.contains(&lazer_entity)
+                && in_rect(lazer_transform, missile_transform, MISSILE_SIZE)
+            {
+                commands.entity(missile_entity).despawn();
+                spend(commands, &mut destroyed, lazer_entity);
+                store.shots_hit += 1;
+                store.add_score(lazer.player, config.score_missile);
+                spawn_explosion(
+                    commands,
+                    &image,
+                    20,
+                    position,
+                    300.0,
+                    0.0,
+                    (10.0, 10.0).into(),
+                );
+                continue 'missiles;
+            }
+        }
+        // hit player
+        if let Some(hit) = player_hit(missile_transform) {
+            commands.entity(missile_entity).despawn();
+            game_state_ew.send(GameStateEvent::LooseLife(hit));
+            play_sound_ew.send(PlaySoundEvent::PlayerDeath);
+            for (lazer_entity, lazer, ..) in &lazer_query {
+                if lazer.player == hit {
+                    spend(commands, &mut destroyed, lazer_entity);
+                }
+            }
+            spawn_explosion(
+                commands,
+                &image,
+                100,
+                position,
+                1000.0,
+                0.0,
+                (10.0, 10.0).into(),
+            );
+        } else {
+            // hit bunker?
+            for (bunker_atlas, bunker_entity, bunker_transform) in &mut bunker_query {
+                if in_rect(missile_transform, bunker_transform, BUNKER_SIZE) {
+                    commands.entity(missile_entity).despawn();
+                    if *state.get() == GameState::Play && destroyed.insert(bunker_entity) {
+                        hit_bunker(commands, bunker_entity, bunker_atlas);
+                        play_sound_ew.send(PlaySoundEvent::BunkerHit);
+                    }
+                    spawn_explosion(
+                        commands,
+                        &image,
+                        20,
+                        position,
+                        200.0,
+                        0.0,
+                        (10.0, 10.0).into(),
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    // each lazer in flight, a bunker, mystery ship or alien is hit only once per tick
+    'lazers: for (lazer_entity, lazer, shot, lazer_transform) in &lazer_query {
+        if destroyed.contains(&lazer_entity) {
+            continue;
+        }
+        let lazer_position = lazer_transform.translation.truncate();
+
         // check bunkers
         for (atlas, entity, bunker_transform) in &mut bunker_query {
             if in_rect(lazer_transform, bunker_transform, BUNKER_SIZE) {
-                hit_bunker(commands, entity, atlas);
-                *lazer = Lazer::Idle;
+                if destroyed.insert(entity) {
+                    hit_bunker(commands, entity, atlas);
+                    play_sound_ew.send(PlaySoundEvent::BunkerHit);
+                }
+                spend(commands, &mut destroyed, lazer_entity);
                 spawn_explosion(
                     commands,
                     &image,
                     5,
-                    (lazer_transform.translation.x, lazer_transform.translation.y).into(),
+                    lazer_position,
                     50.0,
                     0.0,
                     (10.0, 10.0).into(),
                 );
+                continue 'lazers;
+            }
+        }
+
+        // check mystery ship
+        for (mystery_ship_entity, mystery_ship_transform) in &mystery_ship_query {
+            if in_rect(lazer_transform, mystery_ship_transform, MYSTERY_SHIP_SIZE)
+                && destroyed.insert(mystery_ship_entity)
+            {
+                play_sound_ew.send(PlaySoundEvent::MysteryShipHit);
+                commands.entity(mystery_ship_entity).despawn();
+                spend(commands, &mut destroyed, lazer_entity);
+                store.shots_hit += 1;
+                let score = mystery_ship::score(store.shots_fired);
+                store.add_score(lazer.player, score);
+
+                spawn_explosion(
+                    commands,
+                    &image,
+                    50,
+                    lazer_position,
+                    750.0,
+                    0.0,
+                    (10.0, 10.0).into(),
+                );
+                continue 'lazers;
             }
         }
 
         // check aliens
-        for (alien_entity, enemy_transform) in &alien_query {
+        for (alien_entity, enemy_transform, kind, mut hit_points) in &mut alien_query {
             // Collision check
-            if in_rect(lazer_transform, enemy_transform, ALIEN_SIZE) {
-                play_sound_ew.send(PlaySoundEvent::AlienHit);
-                commands.entity(alien_entity).despawn();
-                *lazer = Lazer::Idle;
-                store.aliens_killed += 1;
-                store.alien_speed += ALIENS_SPEED_KILL;
-                store.score += SCORE_ALIEN;
-
+            if !in_rect(lazer_transform, enemy_transform, kind.size())
+                || destroyed.contains(&alien_entity)
+            {
+                continue;
+            }
+            play_sound_ew.send(PlaySoundEvent::AlienHit);
+            store.shots_hit += 1;
+            // a piercing lazer kills regardless of armor, and carries on
+            if shot.piercing {
+                **hit_points = 0;
+            } else {
+                spend(commands, &mut destroyed, lazer_entity);
+                **hit_points = hit_points.saturating_sub(1);
+            }
+            if **hit_points > 0 {
+                // armor hit, the alien survives
                 spawn_explosion(
                     commands,
                     &image,
-                    10,
-                    (lazer_transform.translation.x, lazer_transform.translation.y).into(),
-                    500.0,
+                    5,
+                    lazer_position,
+                    250.0,
                     0.0,
                     (10.0, 10.0).into(),
                 );
+                continue 'lazers;
+            }
+            destroyed.insert(alien_entity);
+            commands.entity(alien_entity).despawn();
+            store.aliens_killed += 1;
+            store.alien_speed += current_wave.speed_kill;
+            store.add_score(lazer.player, kind.score(&config));
+            alien_killed_ew.send(AlienKilledEvent {
+                position: enemy_transform.translation.truncate(),
+            });
 
-                if store.aliens_killed == ALIENS_TOTAL {
-                    debug!("-- send new wave --");
-                    game_state_ew.send(GameStateEvent::NewWave);
-                }
+            spawn_explosion(
+                commands,
+                &image,
+                10,
+                lazer_position,
+                500.0,
+                0.0,
+                (10.0, 10.0).into(),
+            );
+
+            if store.aliens_killed == current_wave.aliens_total() {
+                debug!("-- send new wave --");
+                game_state_ew.send(GameStateEvent::NewWave);
+            }
+            if !shot.piercing {
+                continue 'lazers;
             }
         }
     }
@@ -1,46 +1,41 @@
-use crate::{common::*, game_state::*, lazer::FireLazerEvent, player::PlayerEvent};
+use crate::{
+    action::{self, Action, ActionEvent, Bindings},
+    game_state::*,
+    gamepad::GamepadAssignment,
+    SpaceSet,
+};
 use bevy::prelude::*;
 
-/// keyboard input
+pub struct KeyboardInputPlugin;
+
+impl Plugin for KeyboardInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_system
+                .in_set(SpaceSet::Input)
+                .before(action::update_system),
+        );
+    }
+}
+
+/// keyboard actions, for the first player without a gamepad in co-op (or the player whose turn it is)
 pub fn update_system(
-    mut fire_lazer_ew: EventWriter<FireLazerEvent>,
-    mut game_state_ew: EventWriter<GameStateEvent>,
-    mut player_ew: EventWriter<PlayerEvent>,
+    mut action_ew: EventWriter<ActionEvent>,
+    bindings: Res<Bindings>,
     store: Res<Store>,
+    // the gamepad plugin may be left out
+    assignment: Option<Res<GamepadAssignment>>,
 
     keyboard_input: Res<ButtonInput<KeyCode>>,
 ) {
-    // slow or fast
-    let speed = if keyboard_input.pressed(KeyCode::ShiftLeft) {
-        PLAYER_SLOW
-    } else {
-        1.0
-    };
-    if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
-        player_ew.send(PlayerEvent(-speed));
-    }
-    if keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight) {
-        player_ew.send(PlayerEvent(speed));
-    }
-
-    if keyboard_input.just_pressed(KeyCode::KeyI) {
-        game_state_ew.send(GameStateEvent::Info);
-    }
+    let player = assignment.map_or(store.turn, |assignment| assignment.keyboard_player(&store));
 
-    match store.game_state {
-        GameState::InsertCoin | GameState::LeaderBoard => {
-            if keyboard_input.just_pressed(KeyCode::Enter) {
-                game_state_ew.send(GameStateEvent::PressPlay);
-            }
-        }
-        GameState::PlayerSpawn(_) | GameState::Play => {
-            if keyboard_input.just_pressed(KeyCode::Space)
-                || keyboard_input.pressed(KeyCode::ArrowUp)
-            {
-                debug!("-- fire lazer event sent --");
-                fire_lazer_ew.send(FireLazerEvent);
-            }
+    for action in Action::ALL {
+        let keys = bindings.keys(action);
+        if keyboard_input.any_pressed(keys.iter().copied()) {
+            let just_pressed = keyboard_input.any_just_pressed(keys.iter().copied());
+            action_ew.send(ActionEvent::button(player, action, just_pressed));
         }
-        _ => {}
     }
 }
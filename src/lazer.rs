@@ -1,29 +1,171 @@
-use crate::{common::*, particle::*, player::Player};
+use crate::{
+    audio::PlaySoundEvent, common::*, config::GameConfig, game_state::Store, particle::*,
+    player::Player, rng::GameRng, simulation::Interpolated, weapon::Weapon, SpaceSet,
+};
 use bevy::prelude::*;
-use rand::random;
-use std::time::Duration;
+use rand::Rng;
+use std::{f32::consts::FRAC_PI_2, time::Duration};
 
-#[derive(Component, PartialEq, Clone)]
-pub enum Lazer {
-    Fire,
-    Fired(Timer),
-    Idle,
+pub struct LazerPlugin;
+
+impl Plugin for LazerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FireLazerEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(FixedUpdate, update_system.in_set(SpaceSet::Logic))
+            .add_systems(FixedUpdate, fire_lazer_system.in_set(SpaceSet::Events));
+    }
+}
+
+/// A lazer in flight, despawned on a hit or once out of the scene
+#[derive(Component)]
+pub struct Lazer {
+    /// fired by, scoring the hits
+    pub player: usize,
+    /// between the particles of the trace
+    particle_timer: Timer,
+}
+
+/// The shot of a lazer, set by the weapon when fired
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct LazerShot {
+    /// horizontal offset from the cannon
+    pub offset: f32,
+    pub direction: Vec2,
+    /// passes through aliens, killing them regardless of armor
+    pub piercing: bool,
+}
+
+impl Default for LazerShot {
+    fn default() -> Self {
+        LazerShot {
+            offset: 0.0,
+            direction: Vec2::Y,
+            piercing: false,
+        }
+    }
 }
 
+/// Fire request of a player (player)
 #[derive(Event)]
-pub struct FireLazerEvent;
+pub struct FireLazerEvent(pub usize);
+
+#[derive(Resource)]
+pub struct LazerResource {
+    image_handle: Handle<Image>,
+}
 
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LazerResource {
+        image_handle: asset_server.load("sprites/lazer.png"),
+    });
+}
+
+// the weapon gives the shots, fired once the previous shots of the player are
+// done (with rapid fire, once the last shot is clear of the cannon), as many as
+// allowed in flight for each player
+#[allow(clippy::too_many_arguments)]
 pub fn fire_lazer_system(
+    mut commands: Commands,
     mut fire_lazer_event: EventReader<FireLazerEvent>,
-    mut lazer_query: Query<&mut Lazer>,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    mut store: ResMut<Store>,
+    config: Res<GameConfig>,
+    image: Res<CrossImage>,
+    lazer_resource: Res<LazerResource>,
+    player_query: Query<(&Player, &Transform, &Weapon)>,
+    lazer_query: Query<(&Lazer, &Transform)>,
 ) {
-    if !fire_lazer_event.is_empty() {
+    let mut fire = [false; MAX_PLAYERS];
+    for FireLazerEvent(player) in fire_lazer_event.read() {
         debug!("-- fire lazer event received --");
-        fire_lazer_event.clear();
-        let mut lazer = lazer_query.single_mut();
-        if *lazer == Lazer::Idle {
-            *lazer = Lazer::Fire
+        fire[*player] = true;
+    }
+
+    for (player, player_transform, weapon) in &player_query {
+        if !fire[player.0] {
+            continue;
+        }
+        let lazers: Vec<&Transform> = lazer_query
+            .iter()
+            .filter(|(lazer, _)| lazer.player == player.0)
+            .map(|(_, transform)| transform)
+            .collect();
+        let spacing = if *weapon == Weapon::Rapid {
+            RAPID_SPACING
+        } else {
+            LAZER_SPACING
+        };
+        let free = (config.max_lazers as usize).saturating_sub(lazers.len());
+        let ready = free > 0
+            && lazers.iter().all(|transform| {
+                transform.translation.y > player_transform.translation.y + spacing
+            });
+        if !ready {
+            continue;
         }
+        fire_shots(
+            &mut commands,
+            &image,
+            &lazer_resource,
+            player.0,
+            player_transform,
+            weapon.shots().into_iter().take(free),
+        );
+        store.shots_fired += 1;
+        play_sound_ew.send(PlaySoundEvent::PlayerFire);
+    }
+}
+
+fn fire_shots(
+    commands: &mut Commands,
+    image: &Res<CrossImage>,
+    lazer_resource: &LazerResource,
+    player: usize,
+    player_transform: &Transform,
+    shots: impl Iterator<Item = LazerShot>,
+) {
+    for shot in shots {
+        let transform = Transform {
+            translation: player_transform.translation + Vec3::new(shot.offset, PLAYER_HEIGHT, 0.0),
+            // the sprite points upwards
+            rotation: Quat::from_rotation_z(shot.direction.y.atan2(shot.direction.x) - FRAC_PI_2),
+            ..default()
+        };
+        commands.spawn((
+            Lazer {
+                player,
+                particle_timer: Timer::new(
+                    Duration::from_secs_f32(LAZER_PARTICLE_INTERVAL),
+                    TimerMode::Repeating,
+                ),
+            },
+            shot,
+            Interpolated::new(transform.translation),
+            SpriteBundle {
+                texture: lazer_resource.image_handle.clone(),
+                transform,
+                sprite: Sprite {
+                    color: if shot.piercing {
+                        Color::srgb(0.5, 1.0, 1.0)
+                    } else {
+                        Color::WHITE
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+        spawn_explosion(
+            commands,
+            image,
+            50,
+            transform.translation.truncate(),
+            100.0,
+            0.0,
+            (10.0, 10.0).into(),
+        );
     }
 }
 
@@ -31,68 +173,29 @@ pub fn fire_lazer_system(
 pub fn update_system(
     mut commands: Commands,
     time: Res<Time>,
+    config: Res<GameConfig>,
     image: Res<CrossImage>,
-    mut player_query: Query<&mut Transform, With<Player>>,
-    mut lazer_position: Query<(&mut Lazer, &mut Visibility, &mut Transform), Without<Player>>,
+    mut rng: ResMut<GameRng>,
+    mut lazer_query: Query<(Entity, &mut Lazer, &LazerShot, &mut Transform)>,
 ) {
-    let player_transform = player_query.single_mut();
-    let (mut lazer, mut visibility, mut transform) = lazer_position.single_mut();
-
-    match &mut *lazer {
-        Lazer::Fire => {
-            transform.translation =
-                player_transform.translation + Vec3::new(0.0, PLAYER_HEIGHT, 0.0);
-            *lazer = Lazer::Fired(Timer::new(
-                Duration::from_secs_f32(LAZER_PARTICLE_INTERVAL),
-                TimerMode::Repeating,
-            ));
-            *visibility = Visibility::Visible;
-            spawn_explosion(
+    for (entity, mut lazer, shot, mut transform) in &mut lazer_query {
+        lazer.particle_timer.tick(time.delta());
+        if lazer.particle_timer.just_finished() {
+            spawn_particle(
                 &mut commands,
                 &image,
-                50,
-                (
-                    player_transform.translation.x,
-                    player_transform.translation.y,
-                )
-                    .into(),
-                100.0,
-                0.0,
-                (10.0, 10.0).into(),
+                (transform.translation.x, transform.translation.y).into(),
+                Vec2::new(30.0 * (rng.cosmetic().gen::<f32>() - 0.5), 0.0)
+                    - shot.direction * config.lazer_speed * 0.1,
+                (0.0, 0.0).into(),
             );
         }
-        Lazer::Fired(timer) => {
-            timer.tick(time.delta());
-            if timer.just_finished() {
-                spawn_particle(
-                    commands,
-                    image,
-                    (transform.translation.x, transform.translation.y).into(),
-                    (30.0 * (random::<f32>() - 0.5), -LAZER_SPEED * 0.1).into(),
-                    (0.0, 0.0).into(),
-                );
-            }
 
-            if transform.translation.y > SCENE_HEIGHT {
-                *lazer = Lazer::Idle;
-            } else {
-                transform.translation.y += LAZER_SPEED * time.delta_seconds()
-            }
-        }
-        _ => {
-            *visibility = Visibility::Hidden;
+        if transform.translation.y > SCENE_HEIGHT || transform.translation.x.abs() > SCENE_WIDTH {
+            commands.entity(entity).despawn();
+        } else {
+            transform.translation +=
+                (shot.direction * config.lazer_speed * time.delta_seconds()).extend(0.0);
         }
     }
 }
-
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        Lazer::Idle,
-        SpriteBundle {
-            texture: asset_server.load("sprites/lazer.png"),
-            transform: Transform::from_xyz(0., SCENE_HEIGHT, 0.),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-    ));
-}
@@ -0,0 +1,280 @@
+//! Local leader board, persisted in the user data directory
+//!
+//! A score qualifying for the board is entered arcade style, three letters
+//! picked one at a time, after `GameOver`. Each player has an entry of its own,
+//! entered in turn in a two-player game.
+
+use crate::{
+    common::*,
+    game_state::{GameState, Store, TimerResource},
+    SpaceSet,
+};
+use bevy::prelude::*;
+use std::{
+    io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Leader board, with `path` the file to persist it to (in memory only if None)
+pub struct LeaderBoardPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Default for LeaderBoardPlugin {
+    fn default() -> Self {
+        LeaderBoardPlugin {
+            path: data_dir().map(|dir| dir.join("leader_board.txt")),
+        }
+    }
+}
+
+impl Plugin for LeaderBoardPlugin {
+    fn build(&self, app: &mut App) {
+        let leader_board = match &self.path {
+            Some(path) => LeaderBoard::load(path.clone()),
+            None => LeaderBoard::default(),
+        };
+        app.insert_resource(leader_board)
+            .init_resource::<NameEntry>()
+            .add_event::<NameEntryEvent>()
+            .add_systems(OnEnter(GameState::EnterName), enter_name_system)
+            .add_systems(OnExit(GameState::EnterName), exit_name_system)
+            .add_systems(
+                Update,
+                name_entry_system
+                    .in_set(SpaceSet::Logic)
+                    .run_if(in_state(GameState::EnterName)),
+            );
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+    pub wave: u8,
+    /// seconds since the unix epoch
+    pub date: u64,
+}
+
+impl Entry {
+    // tab separated, name score wave date
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.name, self.score, self.wave, self.date
+        )
+    }
+
+    pub fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let entry = Entry {
+            name: fields.next()?.to_string(),
+            score: fields.next()?.parse().ok()?,
+            wave: fields.next()?.parse().ok()?,
+            date: fields.next()?.parse().ok()?,
+        };
+        fields.next().is_none().then_some(entry)
+    }
+
+    /// The date as YYYY-MM-DD (UTC)
+    pub fn date_string(&self) -> String {
+        // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let z = (self.date / 86400) as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let d = doy - (153 * mp + 2) / 5 + 1;
+        let m = if mp < 10 { mp + 3 } else { mp - 9 };
+        let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+        format!("{:04}-{:02}-{:02}", y, m, d)
+    }
+}
+
+/// The top LEADER_BOARD_ENTRIES, highest score first
+#[derive(Resource, Default, Debug)]
+pub struct LeaderBoard {
+    path: Option<PathBuf>,
+    entries: Vec<Entry>,
+}
+
+impl LeaderBoard {
+    /// Loads the leader board, an empty board if the file is missing
+    pub fn load(path: PathBuf) -> Self {
+        let entries =
+            read_data_file(&path, "leader board").map_or(vec![], |content| Self::parse(&content));
+        LeaderBoard {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    pub fn parse(content: &str) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = content
+            .lines()
+            .filter_map(|line| {
+                let entry = Entry::from_line(line);
+                if entry.is_none() {
+                    warn!("leader board, skipping malformed line {:?}", line);
+                }
+                entry
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(LEADER_BOARD_ENTRIES);
+        entries
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content: String = self
+            .entries
+            .iter()
+            .map(|entry| entry.to_line() + "\n")
+            .collect();
+        write_data_file(path, &content)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// A non zero score beating the lowest entry, or the board is not yet full
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < LEADER_BOARD_ENTRIES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// The first player from `from` on whose score qualifies
+    pub fn qualifying_player(&self, store: &Store, from: usize) -> Option<usize> {
+        (from..store.nr_players).find(|player| self.qualifies(store.players[*player].score))
+    }
+
+    /// Inserts the entry, after entries with the same score
+    pub fn insert(&mut self, entry: Entry) {
+        let index = self.entries.partition_point(|e| e.score >= entry.score);
+        self.entries.insert(index, entry);
+        self.entries.truncate(LEADER_BOARD_ENTRIES);
+    }
+}
+
+/// Arcade style name entry, the letters and the one currently picked
+#[derive(Resource, Default)]
+pub struct NameEntry {
+    pub letters: [u8; NAME_LENGTH],
+    pub index: usize,
+    /// the player entering the name, None once all are entered
+    pub player: Option<usize>,
+    /// the names entered so far, by player
+    pub names: [Option<String>; MAX_PLAYERS],
+}
+
+impl NameEntry {
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.letters).into_owned()
+    }
+}
+
+#[derive(Event, Debug)]
+pub enum NameEntryEvent {
+    Previous,
+    Next,
+    Select,
+}
+
+pub fn enter_name_system(
+    mut name_entry: ResMut<NameEntry>,
+    store: Res<Store>,
+    leader_board: Res<LeaderBoard>,
+) {
+    *name_entry = NameEntry {
+        letters: [b'A'; NAME_LENGTH],
+        index: 0,
+        player: leader_board.qualifying_player(&store, 0),
+        names: default(),
+    };
+}
+
+// the entry of the player, by the name entered so far
+fn commit(
+    name_entry: &mut NameEntry,
+    player: usize,
+    store: &Store,
+    leader_board: &mut LeaderBoard,
+) {
+    let entry = Entry {
+        name: name_entry.name(),
+        score: store.players[player].score,
+        wave: store.wave,
+        date: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    };
+    debug!("leader board entry {:?}", entry);
+    name_entry.names[player] = Some(entry.name.clone());
+    leader_board.insert(entry);
+    if let Err(err) = leader_board.save() {
+        error!("failed to save leader board: {}", err);
+    }
+}
+
+// a name entered, the next qualifying player enters its name
+pub fn name_entry_system(
+    mut name_entry_er: EventReader<NameEntryEvent>,
+    mut name_entry: ResMut<NameEntry>,
+    store: Res<Store>,
+    mut leader_board: ResMut<LeaderBoard>,
+    mut timer: ResMut<TimerResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in name_entry_er.read() {
+        debug!("name entry event {:?}", event);
+        let Some(player) = name_entry.player else {
+            break;
+        };
+        let index = name_entry.index;
+        let letter = &mut name_entry.letters[index];
+        match event {
+            NameEntryEvent::Previous => {
+                *letter = if *letter == b'A' { b'Z' } else { *letter - 1 };
+            }
+            NameEntryEvent::Next => {
+                *letter = if *letter == b'Z' { b'A' } else { *letter + 1 };
+            }
+            NameEntryEvent::Select => {
+                name_entry.index += 1;
+                if name_entry.index == NAME_LENGTH {
+                    commit(&mut name_entry, player, &store, &mut leader_board);
+                    name_entry.player = leader_board.qualifying_player(&store, player + 1);
+                    if name_entry.player.is_some() {
+                        name_entry.letters = [b'A'; NAME_LENGTH];
+                        name_entry.index = 0;
+                        timer.reset();
+                    } else {
+                        next_state.set(GameState::LeaderBoard);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// on time out, the entries left are committed as entered so far
+pub fn exit_name_system(
+    mut name_entry: ResMut<NameEntry>,
+    store: Res<Store>,
+    mut leader_board: ResMut<LeaderBoard>,
+) {
+    let mut player = name_entry.player.take();
+    while let Some(current) = player {
+        commit(&mut name_entry, current, &store, &mut leader_board);
+        name_entry.letters = [b'A'; NAME_LENGTH];
+        player = leader_board.qualifying_player(&store, current + 1);
+    }
+}
@@ -1,12 +1,82 @@
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+pub mod action;
 pub mod alien;
+pub mod alternating;
 pub mod audio;
 pub mod bunker;
 pub mod common;
+pub mod config;
+pub mod difficulty;
 pub mod game_state;
 pub mod gamepad;
+pub mod headless;
 pub mod hit_detection;
 pub mod keyboard_input;
 pub mod lazer;
+pub mod leader_board;
+pub mod march;
+pub mod mystery_ship;
+pub mod online;
 pub mod overlay;
 pub mod particle;
+pub mod pause;
 pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod settings;
+pub mod simulation;
+pub mod wave;
+pub mod weapon;
+
+/// System sets used to order the game systems within `Update` and `FixedUpdate`
+///
+/// In `Update`, input producers run first, then the per frame logic, and finally
+/// the systems consuming events (audio). In `FixedUpdate`, the tick input is
+/// recorded or played back first, then the gameplay logic (movement), collision
+/// detection, and finally the systems consuming the events (firing, state changes).
+/// Systems added by an embedding application can be ordered against these.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SpaceSet {
+    Input,
+    Logic,
+    Collision,
+    Events,
+}
+
+/// All the game subsystems, use `.disable::<T>()` to leave out a subsystem
+///
+/// e.g., `SpaceInvadersPlugin.build().disable::<audio::AudioPlugin>()`
+pub struct SpaceInvadersPlugin;
+
+impl PluginGroup for SpaceInvadersPlugin {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(config::ConfigPlugin::default())
+            .add(simulation::SimulationPlugin::default())
+            .add(simulation::InterpolationPlugin)
+            .add(game_state::GameStatePlugin)
+            .add(pause::PausePlugin)
+            .add(settings::SettingsPlugin::default())
+            .add(wave::WavePlugin::default())
+            .add(difficulty::DifficultyPlugin)
+            .add(alternating::AlternatingPlugin)
+            .add(rng::RngPlugin::default())
+            .add(replay::ReplayPlugin::default())
+            .add(player::PlayerPlugin)
+            .add(lazer::LazerPlugin)
+            .add(weapon::WeaponPlugin)
+            .add(alien::AlienPlugin)
+            .add(bunker::BunkerPlugin)
+            .add(mystery_ship::MysteryShipPlugin)
+            .add(march::MarchPlugin)
+            .add(overlay::OverlayPlugin)
+            .add(particle::ParticlePlugin)
+            .add(audio::AudioPlugin)
+            .add(hit_detection::HitDetectionPlugin)
+            .add(leader_board::LeaderBoardPlugin::default())
+            .add(action::ActionPlugin::default())
+            .add(keyboard_input::KeyboardInputPlugin)
+            .add(gamepad::GamepadPlugin)
+    }
+}
@@ -1,78 +1,132 @@
 //! Space Invaders revisited, why not?
 //! RUST_LOG="bevy-space=info" cargo run
+//!
+//! A run is reproducible given its seed, `cargo run -- --seed <u64>`,
+//! or the `BEVY_SPACE_SEED` environment variable.
+//!
+//! Record the input with `--record <file>`, and play it back with `--replay <file>`.
+//!
+//! Scores are submitted to the online leader board given its url,
+//! `cargo run -- --server <url>`, or the `BEVY_SPACE_SERVER` environment variable.
+//!
+//! The game configuration is read from `--config <file>`, `BEVY_SPACE_CONFIG`,
+//! or `config.ron` in the user data directory if present. Settings are
+//! overridden by `BEVY_SPACE_SET_<NAME>=<value>`, then `--set <name>=<value>`.
 
-use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*, window::WindowResolution};
+use bevy::{prelude::*, window::WindowResolution};
 use bevy_space::{
-    alien, audio, bunker, common::*, game_state, gamepad, hit_detection, keyboard_input, lazer,
-    overlay, particle, player,
+    common::*,
+    config::{ConfigPlugin, GameConfig, CONFIG_ENV_PREFIX},
+    gamepad::GamepadPlugin,
+    keyboard_input::KeyboardInputPlugin,
+    leader_board::LeaderBoardPlugin,
+    online::{HttpBackend, OnlinePlugin},
+    replay::{Replay, ReplayMode, ReplayPlugin},
+    rng::RngPlugin,
+    simulation::SimulationPlugin,
+    SpaceInvadersPlugin,
 };
+use std::path::PathBuf;
 
 fn setup(mut commands: Commands) {
     // we might want to setup a custom camera, for now just default
     commands.spawn(Camera2dBundle::default());
 }
 
+// value of a command line flag, e.g., `--seed 42`
+fn arg(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+// the command line flag takes precedence over the environment
+fn seed() -> Option<u64> {
+    arg("--seed")
+        .or_else(|| std::env::var("BEVY_SPACE_SEED").ok())
+        .map(|seed| seed.parse().expect("seed should be an unsigned integer"))
+}
+
+fn server() -> Option<String> {
+    arg("--server").or_else(|| std::env::var("BEVY_SPACE_SERVER").ok())
+}
+
+// the environment overrides are applied first, so the command line takes precedence
+fn config() -> GameConfig {
+    let path = arg("--config")
+        .or_else(|| std::env::var("BEVY_SPACE_CONFIG").ok())
+        .map(PathBuf::from)
+        .or_else(|| {
+            data_dir()
+                .map(|dir| dir.join("config.ron"))
+                .filter(|path| path.exists())
+        });
+
+    let env = std::env::vars().filter_map(|(name, value)| {
+        let name = name.strip_prefix(CONFIG_ENV_PREFIX)?.to_lowercase();
+        Some((name, value))
+    });
+    let args: Vec<String> = std::env::args().collect();
+    let set = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--set")
+        .map(|pair| {
+            let (name, value) = pair[1]
+                .split_once('=')
+                .expect("setting should be given as <name>=<value>");
+            (name.to_string(), value.to_string())
+        });
+    let overrides: Vec<(String, String)> = env.chain(set).collect();
+
+    GameConfig::load(path.as_deref(), &overrides).expect("failed to load config")
+}
+
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resolution: WindowResolution::new(RES_X, RES_Y),
-                resizable: false,
-                title: "Bevy-Space".to_string(),
-                desired_maximum_frame_latency: core::num::NonZero::new(1u32),
-                ..default()
-            }),
+    let mut plugins = SpaceInvadersPlugin
+        .set(ConfigPlugin { config: config() })
+        .set(RngPlugin { seed: seed() });
+    let mut online = None;
+
+    if let Some(path) = arg("--replay") {
+        let replay = Replay::load(path.as_ref()).expect("failed to load replay");
+        plugins = plugins
+            .set(RngPlugin {
+                seed: Some(replay.seed),
+            })
+            .set(SimulationPlugin {
+                tick_rate: replay.tick_rate,
+            })
+            .set(ReplayPlugin {
+                mode: ReplayMode::Play(replay),
+            })
+            // a replay should not enter the leader board
+            .set(LeaderBoardPlugin { path: None })
+            .disable::<KeyboardInputPlugin>()
+            .disable::<GamepadPlugin>();
+    } else {
+        if let Some(path) = arg("--record") {
+            plugins = plugins.set(ReplayPlugin {
+                mode: ReplayMode::Record(PathBuf::from(path)),
+            });
+        }
+        online = server().map(|url| OnlinePlugin::new(HttpBackend::new(url)));
+    }
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            resolution: WindowResolution::new(RES_X, RES_Y),
+            resizable: false,
+            title: "Bevy-Space".to_string(),
+            desired_maximum_frame_latency: core::num::NonZero::new(1u32),
             ..default()
-        }))
-        .add_plugins(FrameTimeDiagnosticsPlugin)
-        .insert_resource(ClearColor(Color::BLACK))
-        .add_event::<audio::PlaySoundEvent>()
-        .add_event::<audio::PlayMusicEvent>()
-        .add_event::<lazer::FireLazerEvent>()
-        .add_event::<game_state::GameStateEvent>()
-        .add_event::<player::PlayerEvent>()
-        .add_systems(
-            Startup,
-            (
-                setup,
-                game_state::setup,
-                player::setup,
-                lazer::setup,
-                alien::setup,
-                bunker::setup,
-                overlay::setup,
-                particle::setup,
-                audio::setup,
-            )
-                .chain(),
-        )
-        .add_systems(
-            Update,
-            (
-                (
-                    keyboard_input::update_system,
-                    hit_detection::update_system,
-                    player::update_system,
-                    player::blink_update_system,
-                    lazer::update_system,
-                    alien::update_system,
-                    alien::bullet_update_system,
-                    alien::animate_update_system,
-                    overlay::text_update_system,
-                    overlay::score_update_system,
-                    overlay::state_update_system,
-                    game_state::update_system,
-                    particle::update_system,
-                    gamepad::update_system,
-                )
-                    .before(audio::audio_hit_system),
-                (
-                    audio::audio_hit_system,
-                    audio::play_music_system,
-                    lazer::fire_lazer_system,
-                    game_state::game_state_event_system,
-                ),
-            ),
-        )
-        .run();
+        }),
+        ..default()
+    }))
+    .add_plugins(plugins)
+    .insert_resource(ClearColor(Color::BLACK))
+    .add_systems(Startup, setup);
+
+    if let Some(online) = online {
+        app.add_plugins(online);
+    }
+    app.run();
 }
@@ -0,0 +1,79 @@
+//! Marching beat, the classic four note loop of the alien formation
+//!
+//! A note is played for each step of the formation, cycling through four
+//! descending notes. The tempo follows the alien speed and quickens as the
+//! formation thins out. The beat rests outside `GameState::Play`, over the new
+//! wave and player spawn pauses, and along with the frozen game. In game it takes
+//! the place of the title music, paused on insert coin.
+
+use crate::{audio::PlaySoundEvent, common::*, game_state::*, wave::CurrentWave, SpaceSet};
+use bevy::prelude::*;
+use std::time::Duration;
+
+pub struct MarchPlugin;
+
+impl Plugin for MarchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySoundEvent>()
+            .init_resource::<March>()
+            .add_systems(OnEnter(GameState::Start), reset_system)
+            .add_systems(OnEnter(GameState::NewWave), reset_system)
+            .add_systems(OnEnter(GameState::Turn), reset_system)
+            .add_systems(
+                FixedUpdate,
+                march_system
+                    .run_if(in_state(GameState::Play))
+                    .in_set(SpaceSet::Events),
+            );
+    }
+}
+
+/// The marching beat, the next note and the time to it
+#[derive(Resource)]
+pub struct March {
+    /// index of the next note, in `PlaySoundEvent::MARCH`
+    pub step: usize,
+    pub timer: Timer,
+}
+
+impl Default for March {
+    fn default() -> Self {
+        March {
+            step: 0,
+            timer: Timer::from_seconds(MARCH_INTERVAL_MAX, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Seconds between notes, for the alien speed and the aliens left of the formation
+pub fn interval(alien_speed: f32, aliens_left: u8, aliens_total: u8) -> f32 {
+    let left = aliens_left as f32 / aliens_total.max(1) as f32;
+    let scale = MARCH_INTERVAL_LAST + (1.0 - MARCH_INTERVAL_LAST) * left.min(1.0);
+    ((MARCH_STEP / alien_speed).min(MARCH_INTERVAL_MAX) * scale).max(MARCH_INTERVAL_MIN)
+}
+
+// the beat starts over on the first note
+pub fn reset_system(mut march: ResMut<March>) {
+    *march = March::default();
+}
+
+// the interval is updated each tick, so the tempo follows the kills at once
+pub fn march_system(
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    time: Res<Time>,
+    store: Res<Store>,
+    current_wave: Res<CurrentWave>,
+    mut march: ResMut<March>,
+) {
+    let interval = interval(
+        store.alien_speed,
+        store.aliens_left(&current_wave),
+        current_wave.aliens_total(),
+    );
+    march.timer.set_duration(Duration::from_secs_f32(interval));
+    march.timer.tick(time.delta());
+    if march.timer.just_finished() {
+        play_sound_ew.send(PlaySoundEvent::MARCH[march.step]);
+        march.step = (march.step + 1) % PlaySoundEvent::MARCH.len();
+    }
+}
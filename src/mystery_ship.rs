@@ -0,0 +1,174 @@
+//! Mystery ship, the saucer crossing above the alien formation
+//!
+//! As in the original, the bonus for hitting the saucer is picked from a table
+//! by the number of shots fired, so the 23rd shot, and every 15th thereafter,
+//! scores the top bonus. The saucer appears at an interval shortening with each
+//! wave, only while enough aliens remain, and the direction is given by the
+//! parity of the shot count.
+
+use crate::{
+    alternating::swap_system,
+    audio::PlaySoundEvent,
+    common::*,
+    config::GameConfig,
+    game_state::*,
+    simulation::Interpolated,
+    wave::{enter_wave_system, CurrentWave},
+    SpaceSet,
+};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Bonus by shot count (modulo the table length), the classic table
+pub const MYSTERY_SHIP_SCORES: [u32; 15] = [
+    100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100,
+];
+
+pub struct MysteryShipPlugin;
+
+impl Plugin for MysteryShipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySoundEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                OnEnter(GameState::Start),
+                reset_system.after(enter_wave_system),
+            )
+            .add_systems(
+                OnEnter(GameState::NewWave),
+                reset_system.after(enter_wave_system),
+            )
+            .add_systems(OnEnter(GameState::Turn), reset_system.after(swap_system))
+            .add_systems(OnEnter(GameState::GameOver), reset_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_system.run_if(in_state(GameState::Play)),
+                    update_system,
+                )
+                    .chain()
+                    .in_set(SpaceSet::Logic),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct MysteryShip {
+    /// horizontal speed, the sign given by the direction
+    pub speed: f32,
+}
+
+/// The bonus for hitting the mystery ship with the `shots_fired` shot
+pub fn score(shots_fired: u32) -> u32 {
+    MYSTERY_SHIP_SCORES[shots_fired as usize % MYSTERY_SHIP_SCORES.len()]
+}
+
+#[derive(Resource)]
+pub struct MysteryShipResource {
+    image_handle: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    /// time to the next appearance, paused while the saucer is on screen,
+    /// None if the wave has no mystery ship
+    pub spawn_timer: Option<Timer>,
+}
+
+pub fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    // no saucer sprite (yet), a red alien stretched wide will do
+    let layout = TextureAtlasLayout::from_grid(UVec2::new(64, 48), 4, 1, None, None);
+    commands.insert_resource(MysteryShipResource {
+        image_handle: asset_server.load("sprites/alien.png"),
+        layout: texture_atlas_layouts.add(layout),
+        spawn_timer: None,
+    });
+}
+
+// the interval is given by the wave
+pub fn reset_system(
+    mut commands: Commands,
+    current_wave: Res<CurrentWave>,
+    mut mystery_ship_resource: ResMut<MysteryShipResource>,
+    mystery_ship_query: Query<Entity, With<MysteryShip>>,
+) {
+    cleanup_state(&mut commands, mystery_ship_query);
+    mystery_ship_resource.spawn_timer = current_wave
+        .mystery_ship_interval
+        .map(|interval| Timer::new(Duration::from_secs_f32(interval), TimerMode::Once));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_system(
+    mut commands: Commands,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    time: Res<Time>,
+    store: Res<Store>,
+    config: Res<GameConfig>,
+    current_wave: Res<CurrentWave>,
+    mut mystery_ship_resource: ResMut<MysteryShipResource>,
+    mystery_ship_query: Query<(), With<MysteryShip>>,
+) {
+    if !mystery_ship_query.is_empty() {
+        return;
+    }
+    let Some(spawn_timer) = &mut mystery_ship_resource.spawn_timer else {
+        return;
+    };
+    spawn_timer.tick(time.delta());
+    if !spawn_timer.finished() || store.aliens_left(&current_wave) < config.mystery_ship_min_aliens
+    {
+        return;
+    }
+    spawn_timer.reset();
+
+    // enters from the right on an even shot count, as in the original
+    let direction = if store.shots_fired.is_multiple_of(2) {
+        -1.0
+    } else {
+        1.0
+    };
+    let transform = Transform::from_xyz(
+        -direction * (SCENE_WIDTH + MYSTERY_SHIP_SIZE.x),
+        MYSTERY_SHIP_Y,
+        -1.0,
+    );
+    debug!("mystery ship spawned {:?}", transform.translation);
+    play_sound_ew.send(PlaySoundEvent::MysteryShip);
+    commands.spawn((
+        MysteryShip {
+            speed: direction * config.mystery_ship_speed,
+        },
+        Interpolated::new(transform.translation),
+        SpriteBundle {
+            transform,
+            texture: mystery_ship_resource.image_handle.clone(),
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.2, 0.2),
+                custom_size: Some(MYSTERY_SHIP_SIZE),
+                ..default()
+            },
+            ..default()
+        },
+        TextureAtlas {
+            layout: mystery_ship_resource.layout.clone(),
+            index: 0,
+        },
+    ));
+}
+
+/// mystery ship movement, despawned when leaving the screen
+pub fn update_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut mystery_ship_query: Query<(Entity, &MysteryShip, &mut Transform)>,
+) {
+    for (entity, mystery_ship, mut transform) in &mut mystery_ship_query {
+        transform.translation.x += mystery_ship.speed * time.delta_seconds();
+        if transform.translation.x.abs() > SCENE_WIDTH + MYSTERY_SHIP_SIZE.x {
+            trace!("mystery ship despawn");
+            commands.entity(entity).despawn();
+        }
+    }
+}
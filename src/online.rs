@@ -0,0 +1,416 @@
+//! Online leader board, world wide scoring
+//!
+//! A run is submitted once game over is done, with the name if one was entered,
+//! and the world wide top scores are fetched on entering `LeaderBoard`. The
+//! requests are blocking calls to an `OnlineBackend`, run on the `IoTaskPool`
+//! and polled each frame, so a slow server never stalls the game.
+//!
+//! Submissions are queued, and persisted, until accepted by the server, thus
+//! runs played offline are submitted later on.
+
+use crate::{
+    common::*,
+    game_state::{GameState, Store},
+    leader_board::{self, Entry, LeaderBoard, NameEntry},
+    rng::GameRng,
+    SpaceSet,
+};
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+};
+use std::{
+    collections::VecDeque,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Leader board server, the calls are blocking
+pub trait OnlineBackend: Send + Sync {
+    fn submit(&self, submission: &Submission) -> io::Result<()>;
+
+    /// The top `count` entries, highest score first
+    fn fetch(&self, count: usize) -> io::Result<Vec<Entry>>;
+}
+
+/// Online leader board, with `queue_path` the file to persist the queued
+/// submissions to (in memory only if None)
+pub struct OnlinePlugin {
+    pub backend: Arc<dyn OnlineBackend>,
+    pub queue_path: Option<PathBuf>,
+}
+
+impl OnlinePlugin {
+    pub fn new(backend: impl OnlineBackend + 'static) -> Self {
+        OnlinePlugin {
+            backend: Arc::new(backend),
+            queue_path: data_dir().map(|dir| dir.join("online_queue.txt")),
+        }
+    }
+}
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        let queue = match &self.queue_path {
+            Some(path) => SubmissionQueue::load(path.clone()),
+            None => SubmissionQueue::default(),
+        };
+        app.insert_resource(OnlineClient {
+            backend: self.backend.clone(),
+            submit_task: None,
+            fetch_task: None,
+            // runs queued while offline are submitted right away
+            flush: true,
+            retry: Timer::from_seconds(ONLINE_RETRY_INTERVAL, TimerMode::Once),
+        })
+        .insert_resource(queue)
+        .init_resource::<PendingSubmission>()
+        .init_resource::<OnlineLeaderBoard>()
+        .add_systems(OnEnter(GameState::GameOver), game_over_system)
+        .add_systems(
+            OnExit(GameState::EnterName),
+            name_system.after(leader_board::exit_name_system),
+        )
+        .add_systems(OnEnter(GameState::InsertCoin), queue_system)
+        .add_systems(
+            OnEnter(GameState::LeaderBoard),
+            (queue_system, fetch_system).chain(),
+        )
+        .add_systems(
+            Update,
+            (submit_system, fetch_poll_system).in_set(SpaceSet::Logic),
+        );
+    }
+}
+
+/// A finished run, the leader board entry along with the run metadata
+#[derive(Clone, PartialEq, Debug)]
+pub struct Submission {
+    pub entry: Entry,
+    pub seed: u64,
+    pub tick_rate: f64,
+    /// game version
+    pub version: String,
+}
+
+impl Submission {
+    // the entry line, followed by the tab separated metadata
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.entry.to_line(),
+            self.seed,
+            self.tick_rate,
+            self.version
+        )
+    }
+
+    pub fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.rsplitn(4, '\t');
+        let version = fields.next()?.to_string();
+        let tick_rate = fields.next()?.parse().ok()?;
+        let seed = fields.next()?.parse().ok()?;
+        Some(Submission {
+            entry: Entry::from_line(fields.next()?)?,
+            seed,
+            tick_rate,
+            version,
+        })
+    }
+}
+
+/// Submissions not yet accepted by the server, oldest first
+#[derive(Resource, Default, Debug)]
+pub struct SubmissionQueue {
+    path: Option<PathBuf>,
+    submissions: VecDeque<Submission>,
+}
+
+impl SubmissionQueue {
+    pub fn load(path: PathBuf) -> Self {
+        let content = read_data_file(&path, "submission queue").unwrap_or_default();
+        let submissions = content
+            .lines()
+            .filter_map(|line| {
+                let submission = Submission::from_line(line);
+                if submission.is_none() {
+                    warn!("submission queue, skipping malformed line {:?}", line);
+                }
+                submission
+            })
+            .collect();
+        SubmissionQueue {
+            path: Some(path),
+            submissions,
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content: String = self
+            .submissions
+            .iter()
+            .map(|submission| submission.to_line() + "\n")
+            .collect();
+        write_data_file(path, &content)
+    }
+
+    pub fn submissions(&self) -> &VecDeque<Submission> {
+        &self.submissions
+    }
+
+    fn push(&mut self, submission: Submission) {
+        self.submissions.push_back(submission);
+        self.save_or_log();
+    }
+
+    // the first `count` submissions were accepted
+    fn remove(&mut self, count: usize) {
+        self.submissions.drain(..count);
+        self.save_or_log();
+    }
+
+    fn save_or_log(&self) {
+        if let Err(err) = self.save() {
+            error!("failed to save submission queue: {}", err);
+        }
+    }
+}
+
+/// The world wide top scores, empty until fetched
+#[derive(Resource, Default, Debug)]
+pub struct OnlineLeaderBoard {
+    pub entries: Vec<Entry>,
+}
+
+/// The runs of the last game over, by player, submitted once the names are entered
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PendingSubmission(Vec<(usize, Submission)>);
+
+// requests in flight, at most one of each kind
+#[derive(Resource)]
+pub struct OnlineClient {
+    backend: Arc<dyn OnlineBackend>,
+    submit_task: Option<Task<(usize, io::Result<()>)>>,
+    fetch_task: Option<Task<io::Result<Vec<Entry>>>>,
+    flush: bool,
+    retry: Timer,
+}
+
+pub fn game_over_system(
+    store: Res<Store>,
+    rng: Res<GameRng>,
+    time: Res<Time<Fixed>>,
+    mut pending: ResMut<PendingSubmission>,
+) {
+    **pending = (0..store.nr_players)
+        .filter(|player| store.players[*player].score > 0)
+        .map(|player| {
+            let submission = Submission {
+                entry: Entry {
+                    name: "???".to_string(),
+                    score: store.players[player].score,
+                    wave: store.wave,
+                    date: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                },
+                seed: rng.seed(),
+                tick_rate: 1.0 / time.timestep().as_secs_f64(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            };
+            (player, submission)
+        })
+        .collect();
+}
+
+pub fn name_system(name_entry: Res<NameEntry>, mut pending: ResMut<PendingSubmission>) {
+    for (player, submission) in pending.iter_mut() {
+        if let Some(name) = &name_entry.names[*player] {
+            submission.entry.name = name.clone();
+        }
+    }
+}
+
+// game over is done, on entering InsertCoin or LeaderBoard
+pub fn queue_system(
+    mut pending: ResMut<PendingSubmission>,
+    mut queue: ResMut<SubmissionQueue>,
+    mut client: ResMut<OnlineClient>,
+) {
+    for (_, submission) in pending.drain(..) {
+        debug!("queue submission {:?}", submission);
+        queue.push(submission);
+        client.flush = true;
+    }
+}
+
+// submits the queue in order, retried after a while on failure
+pub fn submit_system(
+    time: Res<Time<Real>>,
+    mut client: ResMut<OnlineClient>,
+    mut queue: ResMut<SubmissionQueue>,
+) {
+    client.retry.tick(time.delta());
+
+    if let Some(task) = &mut client.submit_task {
+        let Some((accepted, result)) = block_on(future::poll_once(task)) else {
+            return;
+        };
+        client.submit_task = None;
+        queue.remove(accepted);
+        if let Err(err) = result {
+            warn!("failed to submit score, retrying later: {}", err);
+            client.retry.reset();
+        }
+        return;
+    }
+
+    if queue.submissions.is_empty() || !(client.flush || client.retry.finished()) {
+        return;
+    }
+    client.flush = false;
+    client.retry.reset();
+
+    let backend = client.backend.clone();
+    let submissions: Vec<Submission> = queue.submissions.iter().cloned().collect();
+    client.submit_task = Some(IoTaskPool::get().spawn(async move {
+        for (accepted, submission) in submissions.iter().enumerate() {
+            if let Err(err) = backend.submit(submission) {
+                return (accepted, Err(err));
+            }
+        }
+        (submissions.len(), Ok(()))
+    }));
+}
+
+pub fn fetch_system(mut client: ResMut<OnlineClient>) {
+    if client.fetch_task.is_some() {
+        return;
+    }
+    let backend = client.backend.clone();
+    client.fetch_task =
+        Some(IoTaskPool::get().spawn(async move { backend.fetch(LEADER_BOARD_ENTRIES) }));
+}
+
+pub fn fetch_poll_system(
+    mut client: ResMut<OnlineClient>,
+    mut online_leader_board: ResMut<OnlineLeaderBoard>,
+) {
+    let Some(task) = &mut client.fetch_task else {
+        return;
+    };
+    let Some(result) = block_on(future::poll_once(task)) else {
+        return;
+    };
+    client.fetch_task = None;
+    match result {
+        Ok(entries) => online_leader_board.entries = entries,
+        Err(err) => warn!("failed to fetch the online leader board: {}", err),
+    }
+}
+
+/// HTTP leader board server at `url`
+///
+/// `POST {url}/scores` a submission line, and `GET {url}/scores?count=N` the
+/// top entries, one entry line each.
+pub struct HttpBackend {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpBackend {
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpBackend {
+            url: url.into().trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs_f32(ONLINE_TIMEOUT))
+                .build(),
+        }
+    }
+}
+
+fn http_error(err: ureq::Error) -> io::Error {
+    io::Error::other(err.to_string())
+}
+
+impl OnlineBackend for HttpBackend {
+    fn submit(&self, submission: &Submission) -> io::Result<()> {
+        self.agent
+            .post(&format!("{}/scores", self.url))
+            .send_string(&submission.to_line())
+            .map_err(http_error)?;
+        Ok(())
+    }
+
+    fn fetch(&self, count: usize) -> io::Result<Vec<Entry>> {
+        let content = self
+            .agent
+            .get(&format!("{}/scores", self.url))
+            .query("count", &count.to_string())
+            .call()
+            .map_err(http_error)?
+            .into_string()?;
+        let mut entries = LeaderBoard::parse(&content);
+        entries.truncate(count);
+        Ok(entries)
+    }
+}
+
+/// In-process server, for tests and playing around without a server
+#[derive(Default)]
+pub struct MockBackend {
+    offline: AtomicBool,
+    submissions: Mutex<Vec<Submission>>,
+}
+
+impl MockBackend {
+    /// Requests fail while offline
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn submissions(&self) -> Vec<Submission> {
+        self.submissions.lock().unwrap().clone()
+    }
+
+    fn connect(&self) -> io::Result<()> {
+        if self.offline.load(Ordering::Relaxed) {
+            Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "mock server offline",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl OnlineBackend for MockBackend {
+    fn submit(&self, submission: &Submission) -> io::Result<()> {
+        self.connect()?;
+        self.submissions.lock().unwrap().push(submission.clone());
+        Ok(())
+    }
+
+    fn fetch(&self, count: usize) -> io::Result<Vec<Entry>> {
+        self.connect()?;
+        let mut entries: Vec<Entry> = self
+            .submissions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|submission| submission.entry.clone())
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(count);
+        Ok(entries)
+    }
+}
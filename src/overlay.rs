@@ -2,16 +2,135 @@
 use std::f32::consts::PI;
 
 use bevy::{
-    color::palettes::css::{DARK_CYAN, GOLD, MAGENTA, RED, YELLOW},
+    color::palettes::css::{DARK_CYAN, GOLD, MAGENTA, RED, WHITE, YELLOW},
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
     prelude::*,
 };
 
 use crate::{
+    action::{Action, Bindings, Rebinding},
     common::*,
     game_state::{GameState, Store, TimerResource},
+    leader_board::{Entry, LeaderBoard, NameEntry},
+    online::OnlineLeaderBoard,
+    pause::{PauseItem, PauseMenu, PauseState},
+    settings::{Settings, SettingsItem, SettingsMenu},
+    SpaceSet,
 };
 
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.add_systems(Startup, setup)
+            .add_systems(PostStartup, init_visibility_system)
+            .add_systems(OnEnter(GameState::LeaderBoard), leader_board_update_system)
+            .add_systems(
+                Update,
+                leader_board_update_system.run_if(resource_exists_and_changed::<OnlineLeaderBoard>),
+            )
+            .add_systems(
+                Update,
+                (
+                    text_update_system,
+                    score_update_system,
+                    state_update_system,
+                    insert_coin_update_system.run_if(
+                        in_state(GameState::InsertCoin).and_then(resource_changed::<Bindings>),
+                    ),
+                    name_entry_update_system.run_if(in_state(GameState::EnterName)),
+                    turn_update_system.run_if(in_state(GameState::Turn)),
+                    pause_update_system.run_if(in_state(PauseState::Paused)),
+                    settings_update_system.run_if(in_state(PauseState::Settings)),
+                    controls_update_system.run_if(in_state(PauseState::Controls)),
+                )
+                    .in_set(SpaceSet::Logic),
+            );
+        for game_state in [
+            GameState::GameOver,
+            GameState::InsertCoin,
+            GameState::LeaderBoard,
+            GameState::Start,
+            GameState::NewWave,
+            GameState::EnterName,
+            GameState::Turn,
+        ] {
+            app.add_systems(
+                OnEnter(game_state.clone()),
+                visibility_system(game_state.clone(), Visibility::Visible),
+            )
+            .add_systems(
+                OnExit(game_state.clone()),
+                visibility_system(game_state, Visibility::Hidden),
+            );
+        }
+        for pause_state in [
+            PauseState::Paused,
+            PauseState::Settings,
+            PauseState::Controls,
+        ] {
+            app.add_systems(
+                OnEnter(pause_state.clone()),
+                pause_visibility_system(pause_state.clone(), Visibility::Visible),
+            )
+            .add_systems(
+                OnExit(pause_state.clone()),
+                pause_visibility_system(pause_state, Visibility::Hidden),
+            );
+        }
+    }
+}
+
+// Sets the visibility of the overlay(s) for the given game state
+fn visibility_system(
+    game_state: GameState,
+    visibility: Visibility,
+) -> impl FnMut(Query<(&mut Visibility, &Overlay)>) {
+    move |mut query| {
+        for (mut overlay_visibility, overlay) in &mut query {
+            if overlay.game_state == game_state {
+                *overlay_visibility = visibility;
+            }
+        }
+    }
+}
+
+// Sets the visibility of the menu overlay for the given pause state
+fn pause_visibility_system(
+    pause_state: PauseState,
+    visibility: Visibility,
+) -> impl FnMut(Query<(&mut Visibility, &PauseOverlay)>) {
+    move |mut query| {
+        for (mut overlay_visibility, overlay) in &mut query {
+            if overlay.pause_state == pause_state {
+                *overlay_visibility = visibility;
+            }
+        }
+    }
+}
+
+// The initial OnEnter runs before the overlays are spawned, so show the initial one here
+fn init_visibility_system(
+    state: Res<State<GameState>>,
+    mut query: Query<(&mut Visibility, &Overlay)>,
+    mut pause_query: Query<&mut Visibility, (With<PauseOverlay>, Without<Overlay>)>,
+) {
+    for (mut visibility, overlay) in &mut query {
+        *visibility = if overlay.game_state == *state.get() {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    // the game starts running
+    for mut visibility in &mut pause_query {
+        *visibility = Visibility::Hidden;
+    }
+}
+
 //
 #[derive(Component)]
 pub struct ShowState;
@@ -22,12 +141,39 @@ pub struct Score;
 #[derive(Component)]
 pub struct StatusBar;
 
+#[derive(Component)]
+pub struct LeaderBoardTable;
+
+#[derive(Component)]
+pub struct InsertCoinText;
+
+#[derive(Component)]
+pub struct NameEntryText;
+
+#[derive(Component)]
+pub struct TurnText;
+
+#[derive(Component)]
+pub struct ControlsText;
+
+#[derive(Component)]
+pub struct PauseMenuText;
+
+#[derive(Component)]
+pub struct SettingsText;
+
+/// A menu over the game, shown in its pause state
+#[derive(Component, Debug)]
+pub struct PauseOverlay {
+    pause_state: PauseState,
+}
+
 #[derive(Component, Debug)]
 pub struct Overlay {
     game_state: GameState,
 }
 
-pub fn setup(mut commands: Commands) {
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Show State
     commands.spawn((
         ShowState,
@@ -137,22 +283,29 @@ pub fn setup(mut commands: Commands) {
             ..default()
         }),
     ));
-    // Insert Coin
+    // Insert Coin, the keys are set by the bindings
     commands.spawn((
         Overlay {
             game_state: GameState::InsertCoin,
         },
-        TextBundle::from_section(
-            "   Press Enter\n       to\n   Insert Coin", // Ugly, but works
-            TextStyle {
+        InsertCoinText,
+        TextBundle::from_sections([
+            TextSection::from_style(TextStyle {
                 font_size: INSERT_COIN_FONT_SIZE,
                 color: MAGENTA.into(),
                 ..default()
-            },
-        )
+            }),
+            TextSection::from_style(TextStyle {
+                font_size: INSERT_COIN_HINT_FONT_SIZE,
+                color: WHITE.into(),
+                ..default()
+            }),
+        ])
+        .with_text_justify(JustifyText::Center)
         .with_style(Style {
             position_type: PositionType::Absolute,
             align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
             ..default()
         }),
     ));
@@ -197,16 +350,17 @@ pub fn setup(mut commands: Commands) {
         }),
     ));
 
-    // Leader Board
+    // Turn, the player is filled in while in the state
     commands.spawn((
         Overlay {
-            game_state: GameState::LeaderBoard,
+            game_state: GameState::Turn,
         },
+        TurnText,
         TextBundle::from_section(
-            " Leader Board", // Ugly, but works
+            "",
             TextStyle {
-                font_size: LEADER_BOARD_FONT_SIZE,
-                color: DARK_CYAN.into(),
+                font_size: START_FONT_SIZE,
+                color: YELLOW.into(),
                 ..default()
             },
         )
@@ -216,6 +370,359 @@ pub fn setup(mut commands: Commands) {
             ..default()
         }),
     ));
+
+    // Leader Board, the table is filled in on entering the state
+    let mono = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands.spawn((
+        Overlay {
+            game_state: GameState::LeaderBoard,
+        },
+        LeaderBoardTable,
+        TextBundle::from_sections([
+            TextSection::new(
+                "Leader Board\n",
+                TextStyle {
+                    font_size: LEADER_BOARD_FONT_SIZE,
+                    color: DARK_CYAN.into(),
+                    ..default()
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: mono.clone(),
+                font_size: LEADER_BOARD_TABLE_FONT_SIZE,
+                color: DARK_CYAN.into(),
+            }),
+        ])
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
+            ..default()
+        }),
+    ));
+
+    // Pause, a section per item to highlight the one picked
+    commands.spawn((
+        PauseOverlay {
+            pause_state: PauseState::Paused,
+        },
+        PauseMenuText,
+        TextBundle::from_sections(
+            [TextSection::new(
+                "Paused\n",
+                TextStyle {
+                    font_size: PAUSE_FONT_SIZE,
+                    color: YELLOW.into(),
+                    ..default()
+                },
+            )]
+            .into_iter()
+            .chain(PauseItem::ALL.map(|_| {
+                TextSection::from_style(TextStyle {
+                    font_size: PAUSE_MENU_FONT_SIZE,
+                    color: WHITE.into(),
+                    ..default()
+                })
+            })),
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
+            ..default()
+        }),
+    ));
+
+    // Settings, a section per item to highlight the one picked
+    let item_style = TextStyle {
+        font: mono.clone(),
+        font_size: SETTINGS_FONT_SIZE,
+        color: WHITE.into(),
+    };
+    commands.spawn((
+        PauseOverlay {
+            pause_state: PauseState::Settings,
+        },
+        SettingsText,
+        TextBundle::from_sections(
+            [TextSection::new(
+                "Settings\n",
+                TextStyle {
+                    font_size: LEADER_BOARD_FONT_SIZE,
+                    color: DARK_CYAN.into(),
+                    ..default()
+                },
+            )]
+            .into_iter()
+            .chain(SettingsItem::ALL.map(|_| TextSection::from_style(item_style.clone())))
+            .chain([TextSection::new(
+                "\n\nLeft/Right pick, Fire to change (back with Fine), Start to leave",
+                TextStyle {
+                    color: MAGENTA.into(),
+                    font_size: CONTROLS_FONT_SIZE,
+                    ..item_style
+                },
+            )]),
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
+            ..default()
+        }),
+    ));
+
+    // Controls, a section per action to highlight the one picked
+    let action_style = TextStyle {
+        font: mono.clone(),
+        font_size: CONTROLS_FONT_SIZE,
+        color: WHITE.into(),
+    };
+    commands.spawn((
+        PauseOverlay {
+            pause_state: PauseState::Controls,
+        },
+        ControlsText,
+        TextBundle::from_sections(
+            [TextSection::new(
+                "Controls\n",
+                TextStyle {
+                    font_size: LEADER_BOARD_FONT_SIZE,
+                    color: DARK_CYAN.into(),
+                    ..default()
+                },
+            )]
+            .into_iter()
+            .chain(Action::ALL.map(|_| TextSection::from_style(action_style.clone())))
+            .chain([TextSection::from_style(TextStyle {
+                color: MAGENTA.into(),
+                ..action_style
+            })]),
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
+            ..default()
+        }),
+    ));
+
+    // Enter Name, a section per letter to highlight the one picked
+    let letter_style = TextStyle {
+        font: mono,
+        font_size: NAME_ENTRY_FONT_SIZE,
+        color: WHITE.into(),
+    };
+    commands.spawn((
+        Overlay {
+            game_state: GameState::EnterName,
+        },
+        NameEntryText,
+        TextBundle::from_sections(
+            [TextSection::new(
+                "Enter Your Name\n",
+                TextStyle {
+                    font_size: NAME_ENTRY_FONT_SIZE,
+                    color: MAGENTA.into(),
+                    ..default()
+                },
+            )]
+            .into_iter()
+            .chain((0..NAME_LENGTH).map(|_| TextSection::from_style(letter_style.clone()))),
+        )
+        .with_text_justify(JustifyText::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            width: Val::Percent(100.0),
+            ..default()
+        }),
+    ));
+}
+
+// the leader board plugins may be left out, with the world wide scores
+// the tables are shown side by side, without the wave and date
+pub fn leader_board_update_system(
+    leader_board: Option<Res<LeaderBoard>>,
+    online_leader_board: Option<Res<OnlineLeaderBoard>>,
+    mut query: Query<&mut Text, With<LeaderBoardTable>>,
+) {
+    let local = leader_board
+        .as_ref()
+        .map_or(&[][..], |board| board.entries());
+    let online = online_leader_board
+        .as_ref()
+        .map_or(&[][..], |board| &board.entries[..]);
+    let row = |i: usize, entry: &Entry| format!("{:>2}. {}  {:06}", i + 1, entry.name, entry.score);
+
+    let mut text = query.single_mut();
+    text.sections[1].value = if online.is_empty() {
+        local
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                format!(
+                    "\n{}  W{:<2}  {}",
+                    row(i, entry),
+                    entry.wave,
+                    entry.date_string()
+                )
+            })
+            .collect()
+    } else {
+        let mut value = format!("\n{:<15}    {:<15}", "Local", "World Wide");
+        for i in 0..local.len().max(online.len()) {
+            let local = local.get(i).map_or(String::new(), |entry| row(i, entry));
+            let online = online.get(i).map_or(String::new(), |entry| row(i, entry));
+            value += &format!("\n{:<15}    {:<15}", local, online);
+        }
+        value
+    };
+}
+
+// the first key of the action, e.g., "2" for `Digit2`
+fn key_name(bindings: &Bindings, action: Action) -> String {
+    bindings
+        .keys(action)
+        .first()
+        .map_or("-".to_string(), |key| {
+            let name = format!("{key:?}");
+            name.trim_start_matches("Digit")
+                .trim_start_matches("Key")
+                .to_string()
+        })
+}
+
+// inserting a coin, along with the two-player games and the settings
+pub fn insert_coin_update_system(
+    bindings: Res<Bindings>,
+    mut query: Query<&mut Text, With<InsertCoinText>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[0].value = format!(
+        "Press {}\nto\nInsert Coin\n",
+        key_name(&bindings, Action::Start)
+    );
+    text.sections[1].value = format!(
+        "\n{} for Two Players, Co-op\n{} for Two Players, Alternating\n{} for Settings and Controls",
+        key_name(&bindings, Action::StartCoOp),
+        key_name(&bindings, Action::StartAlternating),
+        key_name(&bindings, Action::Settings)
+    );
+}
+
+// the player entering its name is named in a two-player game
+pub fn name_entry_update_system(
+    name_entry: Option<Res<NameEntry>>,
+    store: Res<Store>,
+    mut query: Query<&mut Text, With<NameEntryText>>,
+) {
+    let Some(name_entry) = name_entry else {
+        return;
+    };
+    let mut text = query.single_mut();
+    text.sections[0].value = match name_entry.player {
+        Some(player) if store.nr_players > 1 => {
+            format!("Player {}, Enter Your Name\n", player + 1)
+        }
+        _ => "Enter Your Name\n".to_string(),
+    };
+    for (i, letter) in name_entry.letters.iter().enumerate() {
+        let section = &mut text.sections[i + 1];
+        section.value = format!(" {} ", *letter as char);
+        section.style.color = if i == name_entry.index {
+            GOLD.into()
+        } else {
+            WHITE.into()
+        };
+    }
+}
+
+pub fn turn_update_system(store: Res<Store>, mut query: Query<&mut Text, With<TurnText>>) {
+    let mut text = query.single_mut();
+    text.sections[0].value = format!("  Player {}", store.turn + 1); // Ugly, but works
+}
+
+pub fn pause_update_system(menu: Res<PauseMenu>, mut query: Query<&mut Text, With<PauseMenuText>>) {
+    let mut text = query.single_mut();
+    for (i, item) in PauseItem::ALL.into_iter().enumerate() {
+        let section = &mut text.sections[i + 1];
+        section.value = format!("  {item:?}  ");
+        section.style.color = if i == menu.selected {
+            GOLD.into()
+        } else {
+            WHITE.into()
+        };
+    }
+}
+
+pub fn settings_update_system(
+    settings: Res<Settings>,
+    menu: Res<SettingsMenu>,
+    mut query: Query<&mut Text, With<SettingsText>>,
+) {
+    let percent = |volume: f32| format!("{:.0}%", volume * 100.0);
+    let mut text = query.single_mut();
+    for (i, item) in SettingsItem::ALL.into_iter().enumerate() {
+        let value = match item {
+            SettingsItem::MasterVolume => percent(settings.master_volume),
+            SettingsItem::MusicVolume => percent(settings.music_volume),
+            SettingsItem::SfxVolume => percent(settings.sfx_volume),
+            SettingsItem::WindowMode => format!("{:?}", settings.window_mode),
+            SettingsItem::Scale => format!("x{}", settings.scale),
+            SettingsItem::ShowFps => (if settings.show_fps { "On" } else { "Off" }).to_string(),
+            SettingsItem::Controls => "...".to_string(),
+        };
+        let section = &mut text.sections[i + 1];
+        section.value = format!("\n{:<16} {:>20}", format!("{item:?}"), value);
+        section.style.color = if i == menu.selected {
+            GOLD.into()
+        } else {
+            WHITE.into()
+        };
+    }
+}
+
+// the keys and buttons of each action, with the help line below
+pub fn controls_update_system(
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    mut query: Query<&mut Text, With<ControlsText>>,
+) {
+    let mut text = query.single_mut();
+    for (i, action) in Action::ALL.into_iter().enumerate() {
+        let keys: Vec<String> = bindings
+            .keys(action)
+            .iter()
+            .map(|k| format!("{k:?}"))
+            .collect();
+        let buttons: Vec<String> = bindings
+            .buttons(action)
+            .iter()
+            .map(|b| format!("{b:?}"))
+            .collect();
+        let section = &mut text.sections[i + 1];
+        section.value = format!(
+            "\n{:<16} {:<24} {:<24}",
+            format!("{action:?}"),
+            keys.join(" "),
+            buttons.join(" ")
+        );
+        section.style.color = if i == rebinding.selected {
+            GOLD.into()
+        } else {
+            WHITE.into()
+        };
+    }
+    text.sections[Action::ALL.len() + 1].value = if rebinding.capturing {
+        format!("\n\nPress a key or button for {:?}", rebinding.action())
+    } else {
+        "\n\nLeft/Right pick, Fire to bind, Start to leave".to_string()
+    };
 }
 
 pub fn text_update_system(
@@ -239,16 +746,26 @@ pub fn score_update_system(
     let mut status_text = status_query.single_mut();
     let mut score_text = score_query.single_mut();
 
-    status_text.sections[1].value = format!("{:1}  ", store.lives);
+    let players = &store.players[..store.nr_players];
+    // in co-op, the lives and scores of each player
+    if let [_] = players {
+        status_text.sections[1].value = format!("{:1}  ", store.lives);
+        score_text.sections[1].value = format!("{:06}", store.score);
+    } else {
+        let lives: Vec<String> = players.iter().map(|p| p.lives.to_string()).collect();
+        let scores: Vec<String> = players.iter().map(|p| format!("{:06}", p.score)).collect();
+        status_text.sections[1].value = format!("{}  ", lives.join("/"));
+        score_text.sections[1].value = scores.join(" ");
+    }
     status_text.sections[3].value = format!("{:1}  ", store.wave);
-    score_text.sections[1].value = format!("{:06}", store.score);
 }
 pub fn state_update_system(
     store: ResMut<Store>,
     game_state_timer: Res<TimerResource>,
+    pause_state: Res<State<PauseState>>,
 
     mut show_state_query: Query<&mut Visibility, With<ShowState>>,
-    mut game_state_query: Query<(&mut Visibility, &mut Text, &Overlay), Without<ShowState>>,
+    mut game_state_query: Query<(&mut Text, &Overlay)>,
 ) {
     let mut show_state_visibilty = show_state_query.single_mut();
     *show_state_visibilty = if store.show_state {
@@ -261,14 +778,18 @@ pub fn state_update_system(
 
     let ratio =
         game_state_timer.elapsed().as_secs_f32() / game_state_timer.duration().as_secs_f32();
-    let alpha = (PI * ratio).sin();
-    for (mut visibility, mut text, overlay) in &mut game_state_query {
-        text.sections[0].style.color.set_alpha(alpha);
-
-        if overlay.game_state == store.game_state {
-            *visibility = Visibility::Visible;
-        } else {
-            *visibility = Visibility::Hidden;
+    // the game state overlay gives way to the menus over the game
+    let alpha = if *pause_state.get() == PauseState::Running {
+        (PI * ratio).sin()
+    } else {
+        0.0
+    };
+    for (mut text, overlay) in &mut game_state_query {
+        // the name entry stays solid, its timer is a time out
+        if overlay.game_state != GameState::EnterName {
+            for section in &mut text.sections {
+                section.style.color.set_alpha(alpha);
+            }
         }
     }
 }
@@ -1,9 +1,22 @@
-use crate::common::*;
+use crate::{common::*, pause::PauseState, rng::GameRng, SpaceSet};
 use bevy::prelude::*;
-use rand::random;
+use rand::Rng;
 use std::f32::consts::TAU;
 use std::time::Duration;
 
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            update_system
+                .run_if(in_state(PauseState::Running))
+                .in_set(SpaceSet::Logic),
+        );
+    }
+}
+
 #[derive(Component)]
 pub struct Particle {
     timer: Timer,
@@ -14,6 +27,7 @@ pub struct Particle {
 pub fn update_system(
     time: Res<Time>,
     mut commands: Commands,
+    mut rng: ResMut<GameRng>,
     mut bullet_query: Query<(Entity, &mut Sprite, &mut Transform, &mut Particle)>,
 ) {
     for (entity, mut sprite, mut transform, mut particle) in &mut bullet_query {
@@ -26,17 +40,19 @@ pub fn update_system(
             commands.entity(entity).despawn();
         } else {
             let translation = &mut transform.translation;
-            translation.x += (particle.delta.x + (random::<f32>() - 0.5) * particle.delta_random.x)
+            translation.x += (particle.delta.x
+                + (rng.cosmetic().gen::<f32>() - 0.5) * particle.delta_random.x)
                 * time.delta_seconds();
-            translation.y += (particle.delta.y + (random::<f32>() - 0.5) * particle.delta_random.y)
+            translation.y += (particle.delta.y
+                + (rng.cosmetic().gen::<f32>() - 0.5) * particle.delta_random.y)
                 * time.delta_seconds();
         }
     }
 }
 
 pub fn spawn_particle(
-    mut commands: Commands,
-    image: Res<CrossImage>,
+    commands: &mut Commands,
+    image: &Res<CrossImage>,
     pos: Vec2,
     delta: Vec2,
     delta_random: Vec2,
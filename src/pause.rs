@@ -0,0 +1,133 @@
+//! Pause, the menus over a frozen game
+//!
+//! Leaving `PauseState::Running` pauses the virtual time, freezing the `FixedUpdate`
+//! gameplay and the game state timer, along with the particles and the alien
+//! animation. The pause menu (resume, restart, settings and quit) is opened in game
+//! by the `Pause` action, and picked from by the `ActionEvent`s.
+
+use crate::{
+    action::{self, Action, ActionEvent},
+    game_state::*,
+    SpaceSet,
+};
+use bevy::prelude::*;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PauseState>()
+            .init_resource::<PauseMenu>()
+            .add_event::<ActionEvent>()
+            .add_event::<GameStateEvent>()
+            .add_systems(OnExit(PauseState::Running), freeze_system)
+            .add_systems(OnEnter(PauseState::Running), thaw_system)
+            .add_systems(
+                Update,
+                menu_system
+                    .in_set(SpaceSet::Input)
+                    .after(action::update_system),
+            );
+    }
+}
+
+/// The menus over the game, the game is frozen unless running
+#[derive(States, Default, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PauseState {
+    #[default]
+    Running,
+    /// the pause menu
+    Paused,
+    /// the settings screen
+    Settings,
+    /// rebinding the keys and gamepad buttons
+    Controls,
+}
+
+/// The pause menu items, in order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseItem {
+    Resume,
+    /// a new game, of the same number of players
+    Restart,
+    Settings,
+    Quit,
+}
+
+impl PauseItem {
+    pub const ALL: [PauseItem; 4] = [
+        PauseItem::Resume,
+        PauseItem::Restart,
+        PauseItem::Settings,
+        PauseItem::Quit,
+    ];
+}
+
+/// The item picked on the pause menu
+#[derive(Resource, Default, Debug)]
+pub struct PauseMenu {
+    pub selected: usize,
+}
+
+impl PauseMenu {
+    pub fn item(&self) -> PauseItem {
+        PauseItem::ALL[self.selected]
+    }
+}
+
+// the game frozen, the pause menu opens on its first item
+pub fn freeze_system(mut time: ResMut<Time<Virtual>>, mut menu: ResMut<PauseMenu>) {
+    debug!("--- Pause ---");
+    time.pause();
+    menu.selected = 0;
+}
+
+pub fn thaw_system(mut time: ResMut<Time<Virtual>>) {
+    debug!("--- Resume ---");
+    time.unpause();
+}
+
+// picking an item with left/right, fire or start to select, pause to resume
+#[allow(clippy::too_many_arguments)]
+pub fn menu_system(
+    mut action_er: EventReader<ActionEvent>,
+    mut game_state_ew: EventWriter<GameStateEvent>,
+    mut app_exit_ew: EventWriter<AppExit>,
+    mut menu: ResMut<PauseMenu>,
+    store: Res<Store>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if *state.get() != PauseState::Paused {
+        action_er.clear();
+        return;
+    }
+    let len = PauseItem::ALL.len();
+    for event in action_er.read().filter(|event| event.just_pressed) {
+        match event.action {
+            Action::MoveLeft => menu.selected = (menu.selected + len - 1) % len,
+            Action::MoveRight => menu.selected = (menu.selected + 1) % len,
+            Action::Pause => {
+                next_state.set(PauseState::Running);
+                return;
+            }
+            // the same key or button may be bound to both
+            Action::Fire | Action::Start => {
+                debug!("pause menu {:?}", menu.item());
+                match menu.item() {
+                    PauseItem::Resume => next_state.set(PauseState::Running),
+                    PauseItem::Restart => {
+                        game_state_ew.send(store.press_play());
+                        next_state.set(PauseState::Running);
+                    }
+                    PauseItem::Settings => next_state.set(PauseState::Settings),
+                    PauseItem::Quit => {
+                        app_exit_ew.send(AppExit::Success);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,57 +1,238 @@
-use crate::{common::*, game_state::*};
+use crate::{
+    common::*,
+    config::GameConfig,
+    game_state::*,
+    simulation::Interpolated,
+    weapon::{Weapon, WeaponTimer},
+    SpaceSet,
+};
 use bevy::prelude::*;
 
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerEvent>()
+            .add_event::<GameStateEvent>()
+            .init_resource::<PlayerInput>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(GameState::Start), start_system)
+            .add_systems(OnEnter(GameState::NewWave), spawn_all_system)
+            .add_systems(OnExit(GameState::PlayerSpawn), show_system)
+            .add_systems(
+                Update,
+                (
+                    input_system,
+                    blink_update_system.run_if(in_state(GameState::PlayerSpawn)),
+                )
+                    .in_set(SpaceSet::Logic),
+            )
+            .add_systems(FixedUpdate, update_system.in_set(SpaceSet::Logic))
+            .add_systems(
+                FixedUpdate,
+                loose_life_system
+                    .after(game_state_event_system)
+                    .in_set(SpaceSet::Events)
+                    .run_if(in_state(GameState::Play)),
+            );
+    }
+}
+
+/// Movement of a player (player, movement)
 #[derive(Event)]
-pub struct PlayerEvent(pub f32);
+pub struct PlayerEvent(pub usize, pub f32);
+
+/// A player ship, by player index (the first player 0)
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Player(pub usize);
 
+/// A player (re)spawning, blinking in `PlayerSpawn`
 #[derive(Component)]
-pub struct Player;
+pub struct Spawning;
+
+/// Movement requested by the `PlayerEvent`s of the last frame, applied on each tick
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PlayerInput(pub [f32; MAX_PLAYERS]);
+
+#[derive(Resource)]
+pub struct PlayerResource {
+    image_handle: Handle<Image>,
+}
+
+/// player input, once per frame
+pub fn input_system(mut player_er: EventReader<PlayerEvent>, mut input: ResMut<PlayerInput>) {
+    **input = [0.0; MAX_PLAYERS];
+    for PlayerEvent(player, movement) in player_er.read() {
+        input[*player] += movement;
+    }
+}
 
 /// player movement
 pub fn update_system(
     time: Res<Time>,
-    mut player_er: EventReader<PlayerEvent>,
-    mut player_query: Query<&mut Transform, With<Player>>,
+    config: Res<GameConfig>,
+    input: Res<PlayerInput>,
+    mut player_query: Query<(&Player, &mut Transform)>,
 ) {
-    let mut transform = player_query.single_mut();
-
-    for event in player_er.read() {
-        if event.0 < 0.0 && transform.translation.x > -SCENE_WIDTH
-            || event.0 > 0.0 && transform.translation.x < SCENE_WIDTH
+    for (player, mut transform) in &mut player_query {
+        let input = input[player.0];
+        if input < 0.0 && transform.translation.x > -SCENE_WIDTH
+            || input > 0.0 && transform.translation.x < SCENE_WIDTH
         {
-            transform.translation.x += event.0 * PLAYER_SPEED * time.delta_seconds()
+            transform.translation.x += input * config.player_speed * time.delta_seconds()
         }
     }
 }
 
-// it uses the shared game_state to determine if visible
-// alternatively one could declare an event to determine state changes
-// but the cost is low so we don't do that
+/// The player ship closest to `position`, if any
+pub fn closest<'a>(
+    players: impl IntoIterator<Item = &'a Transform>,
+    position: Vec2,
+) -> Option<Vec2> {
+    players
+        .into_iter()
+        .map(|transform| transform.translation.truncate())
+        .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+}
+
+// blinks the spawning players, using the shared spawn counter
 pub fn blink_update_system(
+    spawn_counter: Res<SpawnCounter>,
+    mut player_query: Query<&mut Visibility, (With<Player>, With<Spawning>)>,
+) {
+    for mut visibility in &mut player_query {
+        *visibility = if spawn_counter.is_multiple_of(2) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        }
+    }
+}
+
+// the players stay visible when leaving PlayerSpawn
+pub fn show_system(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Visibility), With<Player>>,
+) {
+    for (entity, mut visibility) in &mut player_query {
+        *visibility = Visibility::Visible;
+        commands.entity(entity).remove::<Spawning>();
+    }
+}
+
+// a player hit respawns, or leaves the game when out of lives while the other plays on
+// (taking turns, the ship is handed over to the other player)
+pub fn loose_life_system(
+    mut commands: Commands,
+    mut game_state_er: EventReader<GameStateEvent>,
     store: Res<Store>,
-    mut player_query: Query<&mut Visibility, With<Player>>,
+    player_query: Query<(Entity, &Player)>,
 ) {
-    let mut visibility = player_query.single_mut();
-
-    *visibility = match store.game_state {
-        GameState::PlayerSpawn(spawn_count) => {
-            if spawn_count % 2u8 == 0 {
-                Visibility::Visible
-            } else {
-                Visibility::Hidden
+    for event in game_state_er.read() {
+        let GameStateEvent::LooseLife(hit) = event else {
+            continue;
+        };
+        for (entity, player) in &player_query {
+            if player.0 != *hit {
+                continue;
+            }
+            if store.players[player.0].lives > 0 || store.alternating && store.lives > 0 {
+                commands.entity(entity).insert(Spawning);
+            } else if store.lives > 0 {
+                debug!("player {} out of lives", player.0);
+                commands.entity(entity).despawn();
             }
         }
-        _ => Visibility::Visible,
     }
 }
 
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+// the players of the game are spawned, placed side by side in co-op
+pub fn start_system(
+    mut commands: Commands,
+    store: Res<Store>,
+    player_resource: Res<PlayerResource>,
+    mut player_query: Query<(Entity, &Player, &mut Transform, &mut Interpolated)>,
+) {
+    let position = |player: usize| {
+        Vec3::new(
+            (2.0 * player as f32 - 1.0) * CO_OP_SPAWN_X,
+            -SCENE_HEIGHT,
+            0.0,
+        )
+    };
+    let mut spawned = [false; MAX_PLAYERS];
+    for (entity, player, mut transform, mut interpolated) in &mut player_query {
+        if player.0 >= store.ships() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        spawned[player.0] = true;
+        // a single player starts where the last game ended
+        if store.ships() > 1 {
+            transform.translation = position(player.0);
+            interpolated.teleport();
+        }
+        commands.entity(entity).insert(Spawning);
+    }
+    for player in (0..store.ships()).filter(|player| !spawned[*player]) {
+        let translation = if store.ships() > 1 {
+            position(player)
+        } else {
+            Vec3::new(0.0, -SCENE_HEIGHT, 0.0)
+        };
+        spawn(&mut commands, &player_resource, player, translation);
+    }
+}
+
+// all players spawn with a new wave
+pub fn spawn_all_system(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    for entity in &player_query {
+        commands.entity(entity).insert(Spawning);
+    }
+}
+
+/// The ship color of each player
+pub fn color(player: usize) -> Color {
+    match player {
+        0 => Color::WHITE,
+        _ => Color::srgb(0.6, 1.0, 0.6),
+    }
+}
+
+fn spawn(
+    commands: &mut Commands,
+    player_resource: &PlayerResource,
+    player: usize,
+    translation: Vec3,
+) {
+    let transform = Transform::from_translation(translation);
     commands.spawn((
-        Player,
+        Player(player),
+        Spawning,
+        Weapon::default(),
+        WeaponTimer::default(),
+        Interpolated::new(transform.translation),
         SpriteBundle {
-            texture: asset_server.load("sprites/space.png"),
-            transform: Transform::from_xyz(0., -SCENE_HEIGHT, 0.),
+            texture: player_resource.image_handle.clone(),
+            transform,
+            sprite: Sprite {
+                color: color(player),
+                ..default()
+            },
             ..default()
         },
     ));
 }
+
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let player_resource = PlayerResource {
+        image_handle: asset_server.load("sprites/space.png"),
+    };
+    spawn(
+        &mut commands,
+        &player_resource,
+        0,
+        Vec3::new(0., -SCENE_HEIGHT, 0.),
+    );
+    commands.insert_resource(player_resource);
+}
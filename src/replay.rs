@@ -0,0 +1,304 @@
+//! Input recording and deterministic replay
+//!
+//! The simulation is deterministic given the rng seed, the tick rate and the
+//! input of each tick. A replay holds exactly that, the per tick input is
+//! run-length encoded as most ticks repeat the previous one.
+//!
+//! Recording starts at the first tick, and the replay is saved on entering
+//! `GameOver` and on exit. On playback, the recorded input is fed to the
+//! game instead of the live input.
+//!
+//! Version 2 holds the input of each player, version 1 replays of a single
+//! player still play back.
+
+use crate::{
+    common::MAX_PLAYERS,
+    game_state::{GameState, GameStateEvent},
+    lazer::FireLazerEvent,
+    player::PlayerInput,
+    rng::GameRng,
+    SpaceSet,
+};
+use bevy::prelude::*;
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const MAGIC: &[u8; 4] = b"BSRP";
+const VERSION: u8 = 2;
+
+const FIRE: [u8; MAX_PLAYERS] = [1 << 0, 1 << 4];
+const PRESS_PLAY: u8 = 1 << 1;
+const INFO: u8 = 1 << 2;
+const PRESS_PLAY_CO_OP: u8 = 1 << 3;
+const PRESS_PLAY_ALTERNATING: u8 = 1 << 5;
+
+#[derive(Default, Clone)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    /// Record the input, saved to the given file
+    Record(PathBuf),
+    /// Play back the replay, the live input should be left out
+    Play(Replay),
+}
+
+#[derive(Default)]
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Off => {}
+            ReplayMode::Record(path) => {
+                app.insert_resource(ReplayRecorder {
+                    path: path.clone(),
+                    replay: Replay::new(0, 0.0),
+                })
+                .add_systems(Startup, start_recording_system)
+                .add_systems(FixedUpdate, record_system.in_set(SpaceSet::Input))
+                .add_systems(OnEnter(GameState::GameOver), save_system)
+                .add_systems(Last, save_system.run_if(on_event::<AppExit>()));
+            }
+            ReplayMode::Play(replay) => {
+                app.insert_resource(ReplayPlayer(replay.ticks.iter().copied().collect()))
+                    .add_systems(FixedUpdate, play_system.in_set(SpaceSet::Input));
+            }
+        }
+    }
+}
+
+/// The input of a single tick, movement and fire by player
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct TickInput {
+    pub movement: [f32; MAX_PLAYERS],
+    pub fire: [bool; MAX_PLAYERS],
+    pub press_play: bool,
+    pub press_play_co_op: bool,
+    pub press_play_alternating: bool,
+    pub info: bool,
+}
+
+impl TickInput {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        for (fire, flag) in self.fire.iter().zip(FIRE) {
+            if *fire {
+                flags |= flag;
+            }
+        }
+        if self.press_play {
+            flags |= PRESS_PLAY;
+        }
+        if self.press_play_co_op {
+            flags |= PRESS_PLAY_CO_OP;
+        }
+        if self.press_play_alternating {
+            flags |= PRESS_PLAY_ALTERNATING;
+        }
+        if self.info {
+            flags |= INFO;
+        }
+        flags
+    }
+
+    fn from_flags(movement: [f32; MAX_PLAYERS], flags: u8) -> Self {
+        TickInput {
+            movement,
+            fire: FIRE.map(|flag| flags & flag != 0),
+            press_play: flags & PRESS_PLAY != 0,
+            press_play_co_op: flags & PRESS_PLAY_CO_OP != 0,
+            press_play_alternating: flags & PRESS_PLAY_ALTERNATING != 0,
+            info: flags & INFO != 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub tick_rate: f64,
+    pub ticks: Vec<TickInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, tick_rate: f64) -> Self {
+        Replay {
+            seed,
+            tick_rate,
+            ticks: vec![],
+        }
+    }
+
+    /// Encodes the replay, with the ticks run-length encoded
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut runs: Vec<(u32, TickInput)> = vec![];
+        for tick in &self.ticks {
+            match runs.last_mut() {
+                Some((count, last)) if last == tick && *count < u32::MAX => *count += 1,
+                _ => runs.push((1, *tick)),
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(25 + runs.len() * (5 + 4 * MAX_PLAYERS));
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.tick_rate.to_le_bytes());
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (count, tick) in runs {
+            bytes.extend_from_slice(&count.to_le_bytes());
+            for movement in tick.movement {
+                bytes.extend_from_slice(&movement.to_le_bytes());
+            }
+            bytes.push(tick.flags());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut reader = bytes;
+        let mut take = |n: usize| {
+            if reader.len() < n {
+                return Err(invalid("unexpected end of replay"));
+            }
+            let (head, tail) = reader.split_at(n);
+            reader = tail;
+            Ok(head)
+        };
+
+        if take(4)? != MAGIC {
+            return Err(invalid("not a replay file"));
+        }
+        // version 1, the movement of a single player
+        let players = match take(1)?[0] {
+            1 => 1,
+            VERSION => MAX_PLAYERS,
+            _ => return Err(invalid("unsupported replay version")),
+        };
+        let seed = u64::from_le_bytes(take(8)?.try_into().unwrap());
+        let tick_rate = f64::from_le_bytes(take(8)?.try_into().unwrap());
+        let runs = u32::from_le_bytes(take(4)?.try_into().unwrap());
+
+        let mut replay = Replay::new(seed, tick_rate);
+        for _ in 0..runs {
+            let count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+            let mut movement = [0.0; MAX_PLAYERS];
+            for movement in &mut movement[..players] {
+                *movement = f32::from_le_bytes(take(4)?.try_into().unwrap());
+            }
+            let tick = TickInput::from_flags(movement, take(1)?[0]);
+            replay
+                .ticks
+                .extend(std::iter::repeat_n(tick, count as usize));
+        }
+        Ok(replay)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+/// Remaining ticks to play back
+#[derive(Resource, Deref, DerefMut)]
+pub struct ReplayPlayer(VecDeque<TickInput>);
+
+// the seed and tick rate are known once all plugins are built
+pub fn start_recording_system(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    time: Res<Time<Fixed>>,
+) {
+    recorder.replay = Replay::new(rng.seed(), 1.0 / time.timestep().as_secs_f64());
+}
+
+// records the input of the tick, the events are read before being consumed
+// (the PressPlay variants and Info are the only game state events originating from input)
+pub fn record_system(
+    mut recorder: ResMut<ReplayRecorder>,
+    input: Res<PlayerInput>,
+    mut fire_lazer_er: EventReader<FireLazerEvent>,
+    mut game_state_er: EventReader<GameStateEvent>,
+) {
+    let mut tick = TickInput {
+        movement: **input,
+        ..default()
+    };
+    for FireLazerEvent(player) in fire_lazer_er.read() {
+        tick.fire[*player] = true;
+    }
+    for event in game_state_er.read() {
+        match event {
+            GameStateEvent::PressPlay => tick.press_play = true,
+            GameStateEvent::PressPlayCoOp => tick.press_play_co_op = true,
+            GameStateEvent::PressPlayAlternating => tick.press_play_alternating = true,
+            GameStateEvent::Info => tick.info = true,
+            _ => {}
+        }
+    }
+    recorder.replay.ticks.push(tick);
+}
+
+pub fn save_system(recorder: Res<ReplayRecorder>) {
+    debug!("save replay {:?}", recorder.path);
+    if let Err(err) = recorder.replay.save(&recorder.path) {
+        error!("failed to save replay {:?}: {}", recorder.path, err);
+    }
+}
+
+// feeds the recorded input of the tick
+pub fn play_system(
+    mut player: ResMut<ReplayPlayer>,
+    mut input: ResMut<PlayerInput>,
+    mut fire_lazer_ew: EventWriter<FireLazerEvent>,
+    mut game_state_ew: EventWriter<GameStateEvent>,
+) {
+    let Some(tick) = player.pop_front() else {
+        **input = [0.0; MAX_PLAYERS];
+        return;
+    };
+    if player.is_empty() {
+        info!("replay finished");
+    }
+
+    **input = tick.movement;
+    for (player, fire) in tick.fire.into_iter().enumerate() {
+        if fire {
+            fire_lazer_ew.send(FireLazerEvent(player));
+        }
+    }
+    if tick.press_play {
+        game_state_ew.send(GameStateEvent::PressPlay);
+    }
+    if tick.press_play_co_op {
+        game_state_ew.send(GameStateEvent::PressPlayCoOp);
+    }
+    if tick.press_play_alternating {
+        game_state_ew.send(GameStateEvent::PressPlayAlternating);
+    }
+    if tick.info {
+        game_state_ew.send(GameStateEvent::Info);
+    }
+}
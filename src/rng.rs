@@ -0,0 +1,72 @@
+//! Deterministic random number generation
+//!
+//! Gameplay and cosmetics (particles) draw from separate streams of the same
+//! seed, so the amount of particles on screen never affects the game outcome.
+
+use crate::game_state::GameState;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const GAMEPLAY_STREAM: u64 = 0;
+const COSMETIC_STREAM: u64 = 1;
+
+/// Seeds the `GameRng`, from entropy if no seed given
+#[derive(Default)]
+pub struct RngPlugin {
+    pub seed: Option<u64>,
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        info!("rng seed {}", seed);
+        app.insert_resource(GameRng::new(seed))
+            .add_systems(OnEnter(GameState::Start), reset_system);
+    }
+}
+
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    gameplay: ChaCha8Rng,
+    cosmetic: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let mut gameplay = ChaCha8Rng::seed_from_u64(seed);
+        gameplay.set_stream(GAMEPLAY_STREAM);
+        let mut cosmetic = ChaCha8Rng::seed_from_u64(seed);
+        cosmetic.set_stream(COSMETIC_STREAM);
+        GameRng {
+            seed,
+            gameplay,
+            cosmetic,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restart both streams from the seed
+    pub fn reset(&mut self) {
+        *self = Self::new(self.seed);
+    }
+
+    /// Stream affecting the game outcome
+    pub fn gameplay(&mut self) -> &mut impl Rng {
+        &mut self.gameplay
+    }
+
+    /// Stream for visual effects only
+    pub fn cosmetic(&mut self) -> &mut impl Rng {
+        &mut self.cosmetic
+    }
+}
+
+// each game is played from the seed, regardless of what happened in attract mode
+pub fn reset_system(mut rng: ResMut<GameRng>) {
+    rng.reset();
+}
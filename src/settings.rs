@@ -0,0 +1,301 @@
+//! Settings, the audio, video and controls preferences
+//!
+//! The settings are read from and saved to a RON file, e.g.,
+//! `(music_volume: 0.5, window_mode: BorderlessFullscreen)`, where left out
+//! fields keep their defaults. They are changed on the settings screen, reached
+//! by the `Settings` action from the attract mode or from the pause menu, leading
+//! on to the controls screen for the bindings.
+
+use crate::{
+    action::{self, Action, ActionEvent},
+    common::*,
+    game_state::*,
+    pause::PauseState,
+    SpaceSet,
+};
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+use std::{io, path::PathBuf};
+
+/// Settings, with `path` the settings file (default settings only if None)
+pub struct SettingsPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Default for SettingsPlugin {
+    fn default() -> Self {
+        SettingsPlugin {
+            path: data_dir().map(|dir| dir.join("settings.ron")),
+        }
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = match &self.path {
+            Some(path) => Settings::load(path.clone()),
+            None => Settings::default(),
+        };
+        app.insert_resource(settings)
+            .init_resource::<SettingsMenu>()
+            .add_event::<ActionEvent>()
+            .add_systems(OnExit(PauseState::Settings), save_system)
+            .add_systems(
+                Update,
+                menu_system
+                    .in_set(SpaceSet::Input)
+                    .after(action::update_system),
+            )
+            .add_systems(
+                Update,
+                (window_system, show_fps_system)
+                    .run_if(resource_changed::<Settings>)
+                    .in_set(SpaceSet::Logic),
+            );
+    }
+}
+
+/// The audio, video and display preferences
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// 0..=1, scaling the music and the sound effects
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub window_mode: WindowMode,
+    /// the window scale factor, one of `SETTINGS_SCALES`
+    pub scale: f32,
+    pub show_fps: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            path: None,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            window_mode: WindowMode::Windowed,
+            scale: 1.0,
+            show_fps: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings, the defaults if the file is missing or invalid
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_data_file(&path, "settings", Self::parse);
+        Settings {
+            path: Some(path),
+            ..settings
+        }
+    }
+
+    /// Parses the settings, the volumes clamped to 0..=1 and the scale snapped to
+    /// the nearest of `SETTINGS_SCALES`
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let settings: Settings = ron::from_str(content).map_err(invalid)?;
+        if !settings.scale.is_finite() {
+            return Err(invalid("scale should be a number"));
+        }
+        let scale = SETTINGS_SCALES
+            .into_iter()
+            .min_by(|a, b| {
+                (a - settings.scale)
+                    .abs()
+                    .total_cmp(&(b - settings.scale).abs())
+            })
+            .unwrap_or(1.0);
+        Ok(Settings {
+            scale,
+            master_volume: settings.master_volume.clamp(0.0, 1.0),
+            music_volume: settings.music_volume.clamp(0.0, 1.0),
+            sfx_volume: settings.sfx_volume.clamp(0.0, 1.0),
+            ..settings
+        })
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("settings should serialize")
+    }
+
+    /// Saves the settings, if loaded from a file
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_data_file(path, &self.to_ron())
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    /// Steps the item to its next value (or previous if `back`), wrapping around
+    pub fn step(&mut self, item: SettingsItem, back: bool) {
+        let step = |volume: &mut f32| {
+            let steps = (1.0 / VOLUME_STEP).round() as i32;
+            let current = (*volume / VOLUME_STEP).round() as i32;
+            let next = if back { current - 1 } else { current + 1 };
+            *volume = next.rem_euclid(steps + 1) as f32 * VOLUME_STEP;
+        };
+        let cycle = |len: usize, index: usize| {
+            if back {
+                (index + len - 1) % len
+            } else {
+                (index + 1) % len
+            }
+        };
+        match item {
+            SettingsItem::MasterVolume => step(&mut self.master_volume),
+            SettingsItem::MusicVolume => step(&mut self.music_volume),
+            SettingsItem::SfxVolume => step(&mut self.sfx_volume),
+            SettingsItem::WindowMode => {
+                let index = WINDOW_MODES
+                    .iter()
+                    .position(|mode| *mode == self.window_mode)
+                    .unwrap_or(0);
+                self.window_mode = WINDOW_MODES[cycle(WINDOW_MODES.len(), index)];
+            }
+            SettingsItem::Scale => {
+                let index = SETTINGS_SCALES
+                    .iter()
+                    .position(|scale| *scale >= self.scale)
+                    .unwrap_or(0);
+                self.scale = SETTINGS_SCALES[cycle(SETTINGS_SCALES.len(), index)];
+            }
+            SettingsItem::ShowFps => self.show_fps ^= true,
+            SettingsItem::Controls => {}
+        }
+    }
+}
+
+/// The window modes picked from, in order
+pub const WINDOW_MODES: [WindowMode; 3] = [
+    WindowMode::Windowed,
+    WindowMode::BorderlessFullscreen,
+    WindowMode::Fullscreen,
+];
+
+/// The settings screen items, in order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsItem {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    WindowMode,
+    Scale,
+    ShowFps,
+    /// the controls screen
+    Controls,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 7] = [
+        SettingsItem::MasterVolume,
+        SettingsItem::MusicVolume,
+        SettingsItem::SfxVolume,
+        SettingsItem::WindowMode,
+        SettingsItem::Scale,
+        SettingsItem::ShowFps,
+        SettingsItem::Controls,
+    ];
+}
+
+/// The item picked on the settings screen
+#[derive(Resource, Default, Debug)]
+pub struct SettingsMenu {
+    pub selected: usize,
+}
+
+impl SettingsMenu {
+    pub fn item(&self) -> SettingsItem {
+        SettingsItem::ALL[self.selected]
+    }
+}
+
+// picking an item with left/right, fire to change it (backwards while fine is held),
+// leaving on Start or Pause back to the pause menu in game, otherwise to the attract mode
+pub fn menu_system(
+    mut action_er: EventReader<ActionEvent>,
+    mut settings: ResMut<Settings>,
+    mut menu: ResMut<SettingsMenu>,
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if *pause_state.get() != PauseState::Settings {
+        action_er.clear();
+        return;
+    }
+    let events: Vec<&ActionEvent> = action_er.read().collect();
+    let back = events.iter().any(|event| event.action == Action::Fine);
+    let len = SettingsItem::ALL.len();
+    for event in events.into_iter().filter(|event| event.just_pressed) {
+        match event.action {
+            Action::MoveLeft => menu.selected = (menu.selected + len - 1) % len,
+            Action::MoveRight => menu.selected = (menu.selected + 1) % len,
+            Action::Fire => {
+                debug!("settings {:?}", menu.item());
+                if menu.item() == SettingsItem::Controls {
+                    next_state.set(PauseState::Controls);
+                    return;
+                }
+                settings.step(menu.item(), back);
+                // the same key or button may be bound to start, not leaving the screen
+                return;
+            }
+            Action::Start | Action::Pause => {
+                next_state.set(match state.get() {
+                    GameState::PlayerSpawn | GameState::Play => PauseState::Paused,
+                    _ => PauseState::Running,
+                });
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+// the window mode and scale, left out without a window
+pub fn window_system(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in &mut window_query {
+        window.mode = settings.window_mode;
+        window
+            .resolution
+            .set_scale_factor_override(Some(settings.scale));
+    }
+}
+
+// only on a change of `show_fps`, the info toggle kept over other settings
+pub fn show_fps_system(
+    settings: Res<Settings>,
+    mut store: ResMut<Store>,
+    mut show_fps: Local<Option<bool>>,
+) {
+    if *show_fps != Some(settings.show_fps) {
+        *show_fps = Some(settings.show_fps);
+        store.show_state = settings.show_fps;
+    }
+}
+
+pub fn save_system(settings: Res<Settings>) {
+    if let Err(err) = settings.save() {
+        error!("failed to save settings: {}", err);
+    }
+}
@@ -0,0 +1,126 @@
+//! Fixed timestep simulation
+//!
+//! Gameplay runs in `FixedUpdate` at `tick_rate`, decoupled from the render
+//! frame rate, so fast objects (the lazer) cannot tunnel through aliens and
+//! bunkers at low frame rates. All timers are driven by virtual time, thus
+//! pausing (`Time<Virtual>::pause`) and slow motion (`set_relative_speed`)
+//! apply to the whole game.
+//!
+//! The `InterpolationPlugin` smooths the rendered `Transform` of moving
+//! entities between ticks.
+
+use crate::{common::*, SpaceSet};
+use bevy::{prelude::*, state::state::StateTransition, transform::TransformSystem};
+
+/// Fixed timestep and ordering of the game system sets
+pub struct SimulationPlugin {
+    pub tick_rate: f64,
+}
+
+impl Default for SimulationPlugin {
+    fn default() -> Self {
+        SimulationPlugin {
+            tick_rate: TICK_RATE,
+        }
+    }
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(self.tick_rate))
+            .configure_sets(
+                Update,
+                (SpaceSet::Input, SpaceSet::Logic, SpaceSet::Events).chain(),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SpaceSet::Input,
+                    SpaceSet::Logic,
+                    SpaceSet::Collision,
+                    SpaceSet::Events,
+                )
+                    .chain(),
+            )
+            .add_systems(FixedPreUpdate, state_transition_system);
+    }
+}
+
+// apply pending state transitions on each tick, not only once per frame,
+// so transitions occur at the same tick regardless of the frame rate
+fn state_transition_system(world: &mut World) {
+    let _ = world.try_run_schedule(StateTransition);
+}
+
+/// Interpolates the rendered `Transform` between the last two ticks
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, restore_system)
+            .add_systems(FixedLast, store_system)
+            .add_systems(
+                PostUpdate,
+                interpolate_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Translation at the previous and current tick, for entities moved in `FixedUpdate`
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    rendered: Vec3,
+    teleport: bool,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Interpolated {
+            previous: translation,
+            current: translation,
+            rendered: translation,
+            teleport: false,
+        }
+    }
+
+    /// Don't interpolate the movement of the current tick, e.g., when firing the lazer
+    pub fn teleport(&mut self) {
+        self.teleport = true;
+    }
+}
+
+// restore the simulated translation, unless moved outside the simulation
+fn restore_system(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in &mut query {
+        if transform.translation == interpolated.rendered {
+            transform.translation = interpolated.current;
+        } else {
+            interpolated.current = transform.translation;
+        }
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn store_system(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.current = transform.translation;
+        if interpolated.teleport {
+            interpolated.previous = interpolated.current;
+            interpolated.teleport = false;
+        }
+    }
+}
+
+fn interpolate_system(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut Interpolated)>,
+) {
+    let overstep = time.overstep_fraction();
+    for (mut transform, mut interpolated) in &mut query {
+        let translation = interpolated.previous.lerp(interpolated.current, overstep);
+        transform.translation = translation;
+        interpolated.rendered = translation;
+    }
+}
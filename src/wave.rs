@@ -0,0 +1,279 @@
+//! Wave definitions, loaded from `assets/waves.ron`
+//!
+//! Each wave sets the formation (the alien kind of each row, the number of
+//! columns and the start height), the alien speed and fire rate, the bunkers,
+//! and the special events (mystery ship, homing missiles). Waves past the last
+//! definition repeat it, faster and firing more often for each extra wave.
+//!
+//! The built-in waves are used until the file is loaded, or if it fails to load.
+//! With the `hot_reload` feature, changes to the file are picked up while
+//! running, taking effect from the next wave.
+
+use crate::{
+    alien::{AlienKind, BombKind},
+    common::*,
+    config::GameConfig,
+    game_state::*,
+};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use std::io;
+
+/// Wave definitions, with `path` the asset to load them from (built-in only if None)
+pub struct WavePlugin {
+    pub path: Option<String>,
+}
+
+impl Default for WavePlugin {
+    fn default() -> Self {
+        WavePlugin {
+            path: Some("waves.ron".to_string()),
+        }
+    }
+}
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        let waves = Waves::default();
+        app.insert_resource(CurrentWave(waves.get(1)))
+            .insert_resource(waves)
+            .init_asset::<Waves>()
+            .register_asset_loader(WavesLoader)
+            .add_systems(OnEnter(GameState::Start), enter_wave_system)
+            .add_systems(
+                OnEnter(GameState::NewWave),
+                enter_wave_system.after(enter_new_wave_system),
+            )
+            .add_systems(Update, loaded_system);
+        if let Some(path) = &self.path {
+            let path = path.clone();
+            app.add_systems(
+                Startup,
+                move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(WavesHandle(asset_server.load(&path)));
+                },
+            );
+        }
+    }
+}
+
+/// A wave, as described in the waves file
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Wave {
+    /// alien kind of each row, top row first
+    pub rows: Vec<AlienKind>,
+    pub columns: usize,
+    /// formation top, below the top of the scene
+    pub start_height: f32,
+    /// alien speed at the start of the wave
+    pub speed: f32,
+    /// alien speed added for each alien killed
+    pub speed_kill: f32,
+    /// in seconds, between alien bullets
+    pub bullet_interval: f32,
+    /// bomb kinds the aliens may drop (straight bullets only if left out)
+    #[serde(default = "Wave::default_bombs")]
+    pub bombs: Vec<BombKind>,
+    pub bunkers: usize,
+    /// in seconds, between mystery ship appearances (None for no mystery ship)
+    pub mystery_ship_interval: Option<f32>,
+    pub missiles: bool,
+}
+
+impl Wave {
+    fn default_bombs() -> Vec<BombKind> {
+        vec![BombKind::Straight]
+    }
+
+    pub fn aliens_total(&self) -> u8 {
+        (self.rows.len() * self.columns) as u8
+    }
+}
+
+/// All wave definitions, the first wave first
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, PartialEq, Debug)]
+pub struct Waves {
+    pub waves: Vec<Wave>,
+}
+
+impl Default for Waves {
+    // the classic formation, speeding up and firing more often for each wave,
+    // a new bomb kind for each wave, armored rows from the second wave on, and
+    // homing missiles from the third wave on
+    fn default() -> Self {
+        let wave = |wave: u8| {
+            let n = wave as i32 - 1;
+            let bombs = [
+                BombKind::Straight,
+                BombKind::ZigZag,
+                BombKind::Plunger,
+                BombKind::Squiggly,
+            ];
+            Wave {
+                rows: vec![
+                    AlienKind::Squid,
+                    if wave >= 2 {
+                        AlienKind::ArmoredCrab
+                    } else {
+                        AlienKind::Crab
+                    },
+                    AlienKind::Crab,
+                    if wave >= 3 {
+                        AlienKind::ArmoredOctopus
+                    } else {
+                        AlienKind::Octopus
+                    },
+                    AlienKind::Octopus,
+                ],
+                columns: ALIENS_COL,
+                start_height: 100.0,
+                speed: ALIENS_SPEED_START + n as f32 * ALIENS_SPEED_WAVE,
+                speed_kill: ALIENS_SPEED_KILL,
+                bullet_interval: ALIEN_BULLET_INTERVAL * BULLET_INTERVAL_WAVE.powi(n),
+                bombs: bombs.into_iter().take(wave as usize + 1).collect(),
+                bunkers: BUNKERS,
+                mystery_ship_interval: Some(
+                    MYSTERY_SHIP_INTERVAL * MYSTERY_SHIP_INTERVAL_WAVE.powi(n),
+                ),
+                missiles: wave >= MISSILE_WAVE,
+            }
+        };
+        Waves {
+            waves: (1..=MISSILE_WAVE).map(wave).collect(),
+        }
+    }
+}
+
+impl Waves {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let waves: Waves = ron::de::from_bytes(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if waves.waves.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no waves"));
+        }
+        for (n, wave) in waves.waves.iter().enumerate() {
+            if wave.rows.is_empty() || wave.columns == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("wave {} has no aliens", n + 1),
+                ));
+            }
+            if wave.rows.len() * wave.columns > u8::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("wave {} has too many aliens", n + 1),
+                ));
+            }
+            if wave.bunkers > BUNKERS_MAX {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("wave {} has more than {} bunkers", n + 1, BUNKERS_MAX),
+                ));
+            }
+            let positive = [
+                ("start_height", Some(wave.start_height)),
+                ("speed", Some(wave.speed)),
+                ("speed_kill", Some(wave.speed_kill)),
+                ("bullet_interval", Some(wave.bullet_interval)),
+                ("mystery_ship_interval", wave.mystery_ship_interval),
+            ];
+            for (name, value) in positive {
+                // e.g., a negative or NaN interval, panicking as a duration
+                if value.is_some_and(|value| !value.is_finite() || value <= 0.0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("wave {} {} should be positive", n + 1, name),
+                    ));
+                }
+            }
+        }
+        Ok(waves)
+    }
+
+    /// The definition of `wave` (starting at 1)
+    pub fn get(&self, wave: u8) -> Wave {
+        let last = self.waves.len();
+        let n = wave.max(1) as usize;
+        if n <= last {
+            return self.waves[n - 1].clone();
+        }
+        // past the last definition
+        let extra = (n - last) as i32;
+        let mut wave = self.waves[last - 1].clone();
+        wave.speed += extra as f32 * ALIENS_SPEED_WAVE;
+        wave.bullet_interval *= BULLET_INTERVAL_WAVE.powi(extra);
+        if let Some(interval) = &mut wave.mystery_ship_interval {
+            *interval *= MYSTERY_SHIP_INTERVAL_WAVE.powi(extra);
+        }
+        wave
+    }
+}
+
+/// The wave being played
+#[derive(Resource, Deref)]
+pub struct CurrentWave(pub Wave);
+
+#[derive(Resource)]
+pub struct WavesHandle(Handle<Waves>);
+
+#[derive(Default)]
+pub struct WavesLoader;
+
+impl AssetLoader for WavesLoader {
+    type Asset = Waves;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _: &'a Self::Settings,
+        _: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Waves::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+// the waves in use are replaced once loaded, and on each change of the file
+pub fn loaded_system(
+    mut asset_er: EventReader<AssetEvent<Waves>>,
+    handle: Option<Res<WavesHandle>>,
+    assets: Res<Assets<Waves>>,
+    mut waves: ResMut<Waves>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    for event in asset_er.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(loaded) = assets.get(&handle.0) {
+                info!("waves loaded, {} definitions", loaded.waves.len());
+                *waves = loaded.clone();
+            }
+        }
+    }
+}
+
+// on entering Start and NewWave, the store is set up for the wave
+pub fn enter_wave_system(
+    mut store: ResMut<Store>,
+    config: Res<GameConfig>,
+    waves: Res<Waves>,
+    mut current_wave: ResMut<CurrentWave>,
+) {
+    let wave = waves.get(store.wave);
+    debug!("wave {}: {:?}", store.wave, wave);
+    store.alien_speed = wave.speed;
+    store.bullet_interval = wave.bullet_interval;
+    store.bullet_speed = config.alien_bullet_speed;
+    current_wave.0 = wave;
+}
@@ -0,0 +1,250 @@
+//! Weapons and power-ups
+//!
+//! The `Weapon` of the player gives the shots fired by a `FireLazerEvent`.
+//! Killed aliens may drop a power-up, falling towards the player, picked up
+//! for a limited time. The weapon is lost along with a life.
+
+use crate::{
+    alien::AlienKilledEvent,
+    audio::PlaySoundEvent,
+    common::*,
+    config::GameConfig,
+    game_state::*,
+    lazer::LazerShot,
+    player::{self, Player},
+    rng::GameRng,
+    simulation::Interpolated,
+    SpaceSet,
+};
+use bevy::prelude::*;
+use rand::Rng;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AlienKilledEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(GameState::Start), reset_system)
+            .add_systems(OnEnter(GameState::NewWave), cleanup_system)
+            .add_systems(OnEnter(GameState::Turn), cleanup_system)
+            .add_systems(OnEnter(GameState::GameOver), cleanup_system)
+            .add_systems(
+                FixedUpdate,
+                (
+                    timer_system.run_if(in_state(GameState::Play)),
+                    power_up_update_system.after(player::update_system),
+                )
+                    .in_set(SpaceSet::Logic),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_system.run_if(in_state(GameState::Play)),
+                    loose_life_system.run_if(in_state(GameState::Play)),
+                )
+                    .in_set(SpaceSet::Events),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Weapon {
+    #[default]
+    Single,
+    /// two lazers side by side
+    Double,
+    /// three lazers, fanning out
+    Spread,
+    /// the next shot once the last is clear of the cannon
+    Rapid,
+    /// passes through aliens
+    Piercing,
+}
+
+/// The weapons dropped as power-ups
+pub const POWER_UPS: [Weapon; 4] = [
+    Weapon::Double,
+    Weapon::Spread,
+    Weapon::Rapid,
+    Weapon::Piercing,
+];
+
+impl Weapon {
+    pub fn shots(&self) -> Vec<LazerShot> {
+        let shot = LazerShot::default();
+        match self {
+            Weapon::Single | Weapon::Rapid => vec![shot],
+            Weapon::Double => vec![
+                LazerShot {
+                    offset: -DOUBLE_CANNON_OFFSET,
+                    ..shot
+                },
+                LazerShot {
+                    offset: DOUBLE_CANNON_OFFSET,
+                    ..shot
+                },
+            ],
+            Weapon::Spread => [SPREAD_ANGLE, 0.0, -SPREAD_ANGLE]
+                .into_iter()
+                .map(|angle| LazerShot {
+                    direction: Vec2::from_angle(angle).rotate(Vec2::Y),
+                    ..shot
+                })
+                .collect(),
+            Weapon::Piercing => vec![LazerShot {
+                piercing: true,
+                ..shot
+            }],
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Weapon::Single => Color::WHITE,
+            Weapon::Double => Color::srgb(1.0, 1.0, 0.3),
+            Weapon::Spread => Color::srgb(1.0, 0.3, 1.0),
+            Weapon::Rapid => Color::srgb(0.3, 1.0, 0.3),
+            Weapon::Piercing => Color::srgb(0.5, 1.0, 1.0),
+        }
+    }
+}
+
+/// Time left of a power-up weapon
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct WeaponTimer(pub Timer);
+
+/// A weapon falling towards the player
+#[derive(Component)]
+pub struct PowerUp(pub Weapon);
+
+#[derive(Resource)]
+pub struct WeaponResource {
+    image_handle: Handle<Image>,
+}
+
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // no power-up sprite (yet), a tinted cross will do
+    commands.insert_resource(WeaponResource {
+        image_handle: asset_server.load("sprites/cross.png"),
+    });
+}
+
+fn arm(weapon: &mut Weapon, timer: &mut WeaponTimer, armed: Weapon, duration: f32) {
+    *weapon = armed;
+    timer.0 = Timer::from_seconds(duration, TimerMode::Once);
+}
+
+// a new game, back to the single cannon
+pub fn reset_system(
+    mut commands: Commands,
+    mut player_query: Query<(&mut Weapon, &mut WeaponTimer), With<Player>>,
+    power_up_query: Query<Entity, With<PowerUp>>,
+) {
+    cleanup_state(&mut commands, power_up_query);
+    for (mut weapon, mut timer) in &mut player_query {
+        arm(&mut weapon, &mut timer, Weapon::Single, 0.0);
+    }
+}
+
+pub fn cleanup_system(mut commands: Commands, power_up_query: Query<Entity, With<PowerUp>>) {
+    cleanup_state(&mut commands, power_up_query);
+}
+
+pub fn loose_life_system(
+    mut game_state_er: EventReader<GameStateEvent>,
+    mut player_query: Query<(&Player, &mut Weapon, &mut WeaponTimer)>,
+) {
+    for event in game_state_er.read() {
+        let GameStateEvent::LooseLife(hit) = event else {
+            continue;
+        };
+        for (player, mut weapon, mut timer) in &mut player_query {
+            if player.0 == *hit {
+                arm(&mut weapon, &mut timer, Weapon::Single, 0.0);
+            }
+        }
+    }
+}
+
+pub fn timer_system(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Weapon, &mut WeaponTimer), With<Player>>,
+) {
+    for (mut weapon, mut timer) in &mut player_query {
+        timer.tick(time.delta());
+        if *weapon != Weapon::Single && timer.just_finished() {
+            debug!("power-up {:?} timed out", *weapon);
+            *weapon = Weapon::Single;
+        }
+    }
+}
+
+pub fn drop_system(
+    mut commands: Commands,
+    mut alien_killed_er: EventReader<AlienKilledEvent>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    weapon_resource: Res<WeaponResource>,
+) {
+    for event in alien_killed_er.read() {
+        if rng.gameplay().gen::<f32>() >= config.power_up_probability {
+            continue;
+        }
+        let weapon = POWER_UPS[rng.gameplay().gen_range(0..POWER_UPS.len())];
+        debug!("power-up {:?} dropped at {:?}", weapon, event.position);
+        let transform = Transform::from_translation(event.position.extend(0.0));
+        commands.spawn((
+            PowerUp(weapon),
+            Interpolated::new(transform.translation),
+            SpriteBundle {
+                transform,
+                texture: weapon_resource.image_handle.clone(),
+                sprite: Sprite {
+                    color: weapon.color(),
+                    custom_size: Some(POWER_UP_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+/// power-up movement, picked up by the first player touching it
+pub fn power_up_update_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    state: Res<State<GameState>>,
+    mut play_sound_ew: EventWriter<PlaySoundEvent>,
+    mut player_query: Query<(&Transform, &mut Weapon, &mut WeaponTimer), With<Player>>,
+    mut power_up_query: Query<(Entity, &PowerUp, &mut Transform), Without<Player>>,
+) {
+    'power_ups: for (entity, power_up, mut transform) in &mut power_up_query {
+        transform.translation.y -= POWER_UP_SPEED * time.delta_seconds();
+        let position = transform.translation.truncate();
+        if *state.get() == GameState::Play {
+            for (player_transform, mut weapon, mut timer) in &mut player_query {
+                let player =
+                    Rect::from_center_size(player_transform.translation.truncate(), PLAYER_SIZE);
+                if player.contains(position) {
+                    debug!("power-up {:?} picked up", power_up.0);
+                    play_sound_ew.send(PlaySoundEvent::PowerUp);
+                    arm(
+                        &mut weapon,
+                        &mut timer,
+                        power_up.0,
+                        config.power_up_duration,
+                    );
+                    commands.entity(entity).despawn();
+                    continue 'power_ups;
+                }
+            }
+        }
+        if position.y < -SCENE_HEIGHT - POWER_UP_SIZE.y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy_space::{
+    action::{Action, ActionEvent, Bindings, Rebinding},
+    game_state::{GameState, Store},
+    headless,
+    lazer::Lazer,
+    pause::PauseState,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn bindings_keep_defaults_left_out() {
+    let bindings = Bindings::parse("(keys: {Fire: [KeyW]}, buttons: {Pause: [Select]})").unwrap();
+    let defaults = Bindings::default();
+    assert_eq!(bindings.keys(Action::Fire), &[KeyCode::KeyW]);
+    assert_eq!(
+        bindings.buttons(Action::Pause),
+        &[GamepadButtonType::Select]
+    );
+    assert_eq!(
+        bindings.keys(Action::MoveLeft),
+        defaults.keys(Action::MoveLeft)
+    );
+    assert_eq!(
+        bindings.buttons(Action::Fire),
+        defaults.buttons(Action::Fire)
+    );
+
+    assert!(Bindings::parse("(keys: {Jump: [Space]})").is_err());
+    assert!(Bindings::parse("(axes: {})").is_err());
+}
+
+#[test]
+fn bindings_saved_and_loaded() {
+    let path = std::env::temp_dir().join(format!("bevy_space_bindings_{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut bindings = Bindings::load(path.clone());
+    assert_eq!(bindings, Bindings::load(path.clone()));
+    bindings.bind_key(Action::Fire, KeyCode::KeyK);
+    bindings.bind_button(Action::Fire, GamepadButtonType::East);
+    bindings.save().unwrap();
+
+    let loaded = Bindings::load(path.clone());
+    assert_eq!(loaded.keys(Action::Fire), &[KeyCode::KeyK]);
+    assert_eq!(loaded.buttons(Action::Fire), &[GamepadButtonType::East]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn actions_play_the_game() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+    assert_eq!(app.world().resource::<Store>().nr_players, 1);
+
+    press(&mut app, Action::Fire);
+    run_until(&mut app, |app| count::<With<Lazer>>(app) > 0);
+}
+
+#[test]
+fn autofire_keeps_firing_while_held() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+
+    let hold = |app: &mut App, action: Action| {
+        for _ in 0..300 {
+            app.world_mut()
+                .send_event(ActionEvent::button(0, action, false));
+            app.update();
+        }
+        app.world().resource::<Store>().shots_fired
+    };
+    // fire held down is a single shot
+    assert_eq!(hold(&mut app, Action::Fire), 0);
+    assert!(hold(&mut app, Action::AutoFire) > 1);
+}
+
+#[test]
+fn controls_screen_picks_the_action() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    press(&mut app, Action::Settings);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Settings);
+    // the controls, last of the settings
+    press(&mut app, Action::MoveLeft);
+    press(&mut app, Action::Fire);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Controls);
+
+    press(&mut app, Action::MoveRight);
+    press(&mut app, Action::MoveRight);
+    assert_eq!(app.world().resource::<Rebinding>().action(), Action::Fine);
+
+    press(&mut app, Action::MoveLeft);
+    press(&mut app, Action::Fire);
+    let rebinding = app.world().resource::<Rebinding>();
+    assert_eq!(rebinding.action(), Action::MoveRight);
+    assert!(rebinding.capturing);
+
+    // no input devices to bind from, the capture is given up
+    app.world_mut().resource_mut::<Rebinding>().capturing = false;
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Settings);
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Running);
+    assert_eq!(state(&app), GameState::InsertCoin);
+}
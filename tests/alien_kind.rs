@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienKind, HitPoints},
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    lazer::FireLazerEvent,
+    player::Player,
+    wave::Waves,
+};
+
+mod common;
+use common::*;
+
+fn kinds(app: &mut App) -> Vec<(f32, AlienKind)> {
+    let world = app.world_mut();
+    world
+        .query_filtered::<(&Transform, &AlienKind), With<Alien>>()
+        .iter(world)
+        .map(|(transform, kind)| (transform.translation.y, *kind))
+        .collect()
+}
+
+fn hit_points(app: &mut App, alien: Entity) -> Option<u8> {
+    app.world()
+        .get::<HitPoints>(alien)
+        .map(|hit_points| **hit_points)
+}
+
+// place the player below the alien and fire
+fn fire_at(app: &mut App, alien: Entity) {
+    let world = app.world_mut();
+    let x = world.get::<Transform>(alien).unwrap().translation.x;
+    world
+        .query_filtered::<&mut Transform, With<Player>>()
+        .single_mut(world)
+        .translation
+        .x = x;
+    world.send_event(FireLazerEvent(0));
+}
+
+#[test]
+fn formation_rows() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+
+    let kinds = kinds(&mut app);
+    assert_eq!(kinds.len(), ALIENS_TOTAL as usize);
+    let top = kinds.iter().fold(f32::MIN, |top, (y, _)| top.max(*y));
+    let bottom = kinds.iter().fold(f32::MAX, |bottom, (y, _)| bottom.min(*y));
+    for (y, kind) in kinds {
+        if y == top {
+            assert_eq!(kind, AlienKind::Squid);
+        } else if y == bottom {
+            assert_eq!(kind, AlienKind::Octopus);
+        }
+    }
+    assert_eq!(
+        AlienKind::Octopus.score(&GameConfig::default()),
+        GameConfig::default().score_alien
+    );
+    assert!(
+        AlienKind::Squid.score(&GameConfig::default())
+            > AlienKind::Crab.score(&GameConfig::default())
+    );
+    assert!(AlienKind::Squid.fire().missiles);
+    assert!(!AlienKind::Octopus.fire().missiles);
+}
+
+#[test]
+fn armored_alien_takes_two_hits() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    for wave in &mut app.world_mut().resource_mut::<Waves>().waves {
+        wave.rows = vec![AlienKind::ArmoredOctopus; ALIENS_ROW];
+    }
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+
+    // clear the line of fire
+    despawn_all::<With<Bunker>>(&mut app);
+    let world = app.world_mut();
+
+    // the bottom alien of the left most column
+    let (alien, kind) = world
+        .query_filtered::<(Entity, &Transform, &AlienKind), With<Alien>>()
+        .iter(world)
+        .min_by(|(_, a, _), (_, b, _)| {
+            (a.translation.x, a.translation.y)
+                .partial_cmp(&(b.translation.x, b.translation.y))
+                .unwrap()
+        })
+        .map(|(entity, _, kind)| (entity, *kind))
+        .unwrap();
+    assert_eq!(kind, AlienKind::ArmoredOctopus);
+    assert_eq!(hit_points(&mut app, alien), Some(2));
+
+    // the armor takes the first hit
+    fire_at(&mut app, alien);
+    run_until(&mut app, |app| hit_points(app, alien) == Some(1));
+    assert_eq!(app.world().resource::<Store>().score, 0);
+    assert_eq!(app.world().resource::<Store>().aliens_killed, 0);
+
+    // killed by the second
+    run_until(&mut app, |app| {
+        let killed = hit_points(app, alien).is_none();
+        if !killed {
+            fire_at(app, alien);
+        }
+        killed
+    });
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.aliens_killed, 1);
+    assert_eq!(
+        store.score,
+        AlienKind::ArmoredOctopus.score(&GameConfig::default())
+    );
+}
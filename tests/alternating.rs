@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet},
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    player::Player,
+};
+
+mod common;
+use common::*;
+
+fn alternating() -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    start(&mut app, GameStateEvent::PressPlayAlternating);
+    hold_fire(&mut app);
+    app
+}
+
+fn ship(app: &mut App) -> Player {
+    let world = app.world_mut();
+    *world.query::<&Player>().single(world)
+}
+
+// drop an alien bullet right on the ship, and wait for the next turn
+fn hit(app: &mut App) {
+    let world = app.world_mut();
+    let transform = *world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world);
+    world.spawn((AlienBullet, transform));
+    run_until(app, |app| state(app) == GameState::Turn);
+    run_until(app, |app| state(app) == GameState::Play);
+    hold_fire(app);
+}
+
+// some of the aliens and bunker blocks destroyed
+fn destroy<T: Component>(app: &mut App, n: usize) {
+    let world = app.world_mut();
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, With<T>>()
+        .iter(world)
+        .take(n)
+        .collect();
+    for entity in entities {
+        world.despawn(entity);
+    }
+}
+
+#[test]
+fn alternating_start_has_one_ship() {
+    let mut app = alternating();
+    assert_eq!(ship(&mut app), Player(0));
+
+    let lives = GameConfig::default().nr_lives;
+    let store = app.world().resource::<Store>();
+    assert!(store.alternating);
+    assert_eq!(store.nr_players, 2);
+    assert_eq!(store.lives, 2 * lives);
+}
+
+#[test]
+fn turns_keep_the_wave_of_each_player() {
+    let mut app = alternating();
+    let bunkers = count::<With<Bunker>>(&mut app);
+    destroy::<Alien>(&mut app, 3);
+    destroy::<Bunker>(&mut app, 2);
+    app.world_mut().resource_mut::<Store>().aliens_killed = 3;
+
+    // the second player starts from the first wave
+    hit(&mut app);
+    assert_eq!(ship(&mut app), Player(1));
+    assert_eq!(count::<With<Alien>>(&mut app), ALIENS_TOTAL as usize);
+    assert_eq!(count::<With<Bunker>>(&mut app), bunkers);
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.turn, 1);
+    assert_eq!(store.aliens_killed, 0);
+    assert_eq!(store.players[0].lives, GameConfig::default().nr_lives - 1);
+
+    // back to the first player, as left
+    hit(&mut app);
+    assert_eq!(ship(&mut app), Player(0));
+    assert_eq!(count::<With<Alien>>(&mut app), ALIENS_TOTAL as usize - 3);
+    assert_eq!(count::<With<Bunker>>(&mut app), bunkers - 2);
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.turn, 0);
+    assert_eq!(store.aliens_killed, 3);
+}
+
+#[test]
+fn out_of_lives_the_other_keeps_the_ship() {
+    let mut app = alternating();
+    app.world_mut().resource_mut::<Store>().players[1].lives = 0;
+
+    // no turn to take, the first player respawns
+    let world = app.world_mut();
+    let transform = *world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world);
+    world.spawn((AlienBullet, transform));
+    run_until(&mut app, |app| state(app) != GameState::Play);
+    assert_eq!(state(&app), GameState::PlayerSpawn);
+    assert_eq!(ship(&mut app), Player(0));
+}
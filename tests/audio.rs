@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_space::{
+    audio::{self, PlayMusicEvent, PlaySoundEvent, SoundManifest},
+    game_state::GameStateEvent,
+    headless,
+    lazer::FireLazerEvent,
+};
+
+mod common;
+use common::*;
+
+// the sounds played so far
+#[derive(Resource, Default)]
+struct Played(Vec<PlaySoundEvent>);
+
+fn collect_system(mut play_sound_er: EventReader<PlaySoundEvent>, mut played: ResMut<Played>) {
+    played.0.extend(play_sound_er.read().copied());
+}
+
+fn played(app: &App, sound: PlaySoundEvent) -> bool {
+    app.world().resource::<Played>().0.contains(&sound)
+}
+
+#[test]
+fn manifest_has_every_sound() {
+    let manifest = SoundManifest::parse(&std::fs::read("assets/sounds.ron").unwrap()).unwrap();
+    for sound in PlaySoundEvent::ALL {
+        assert!(manifest.sounds.contains_key(&sound), "{:?} left out", sound);
+    }
+    // each sound told apart, by its sample or pitch
+    for (sound, entry) in &manifest.sounds {
+        for (other, other_entry) in &manifest.sounds {
+            assert!(
+                sound == other
+                    || entry.file != other_entry.file
+                    || entry.speed != other_entry.speed,
+                "{:?} sounds as {:?}",
+                sound,
+                other
+            );
+        }
+    }
+}
+
+#[test]
+fn manifest_rejects_invalid_sounds() {
+    let parse = |content: &str| SoundManifest::parse(content.as_bytes());
+    let manifest = parse(r#"(sounds: {AlienHit: (file: "hit.ogg")})"#).unwrap();
+    let entry = &manifest.sounds[&PlaySoundEvent::AlienHit];
+    assert_eq!((entry.speed, entry.volume), (1.0, 1.0));
+
+    assert!(parse(r#"(sounds: {Jump: (file: "jump.ogg")})"#).is_err());
+    assert!(parse(r#"(sounds: {AlienHit: (file: "hit.ogg", speed: 0.0)})"#).is_err());
+    assert!(parse(r#"(sounds: {AlienHit: (file: "hit.ogg", volume: 2.0)})"#).is_err());
+}
+
+#[test]
+fn music_without_sink() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_event::<PlayMusicEvent>()
+        .add_systems(Update, audio::play_music_system);
+    app.world_mut().send_event(PlayMusicEvent(true));
+    app.update();
+    app.world_mut().send_event(PlayMusicEvent(false));
+    app.update();
+}
+
+#[test]
+fn game_events_play_sounds() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.init_resource::<Played>()
+        .add_systems(Last, collect_system);
+    start(&mut app, GameStateEvent::PressPlay);
+    assert!(played(&app, PlaySoundEvent::InsertCoin));
+
+    app.world_mut().send_event(FireLazerEvent(0));
+    run_until(&mut app, |app| played(app, PlaySoundEvent::PlayerFire));
+    // the aliens fire back
+    run_until(&mut app, |app| played(app, PlaySoundEvent::AlienBomb));
+
+    app.world_mut().send_event(GameStateEvent::NewWave);
+    run_until(&mut app, |app| played(app, PlaySoundEvent::WaveClear));
+}
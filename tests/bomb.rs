@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet, AlienKind, Bomb, BombKind},
+    player::Player,
+    wave::Waves,
+};
+
+mod common;
+use common::*;
+
+// play without aliens, so only the bombs spawned by the test are falling
+fn play_without_aliens() -> App {
+    let mut app = play();
+    despawn_all::<With<Alien>>(&mut app);
+    app
+}
+
+fn drop_bomb(app: &mut App, kind: BombKind, position: Vec2) -> Entity {
+    let transform = Transform::from_translation(position.extend(0.0));
+    app.world_mut()
+        .spawn((AlienBullet, Bomb::new(kind, position.x), transform))
+        .id()
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world()
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+#[test]
+fn bomb_kinds_by_alien_and_wave() {
+    let waves = Waves::default();
+    assert_eq!(waves.get(1).bombs, [BombKind::Straight, BombKind::ZigZag]);
+    assert!(waves.get(3).bombs.contains(&BombKind::Squiggly));
+    assert!(!AlienKind::Octopus
+        .fire()
+        .bombs
+        .contains(&BombKind::Squiggly));
+    assert!(BombKind::Plunger.speed() > BombKind::Straight.speed());
+    assert!(BombKind::ZigZag.speed() < BombKind::Straight.speed());
+}
+
+#[test]
+fn bombs_fall_by_kind() {
+    let mut app = play_without_aliens();
+    let start = Vec2::new(-200.0, 200.0);
+    let straight = drop_bomb(&mut app, BombKind::Straight, start);
+    let zigzag = drop_bomb(&mut app, BombKind::ZigZag, start);
+    let plunger = drop_bomb(&mut app, BombKind::Plunger, start);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let straight = position(&app, straight);
+    let zigzag = position(&app, zigzag);
+    let plunger = position(&app, plunger);
+    assert_eq!(straight.x, start.x);
+    assert_ne!(zigzag.x, start.x);
+    assert!(plunger.y < straight.y && straight.y < zigzag.y);
+}
+
+#[test]
+fn squiggly_tracks_the_player() {
+    let mut app = play_without_aliens();
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation
+        .x;
+    let squiggly = drop_bomb(
+        &mut app,
+        BombKind::Squiggly,
+        Vec2::new(player + 200.0, 200.0),
+    );
+    for _ in 0..30 {
+        app.update();
+    }
+    let x = position(&app, squiggly).x;
+    assert!(x < player + 200.0 && x > player);
+}
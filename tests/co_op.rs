@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet},
+    bunker::Bunker,
+    config::GameConfig,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    lazer::FireLazerEvent,
+    leader_board::{LeaderBoard, NameEntry, NameEntryEvent},
+    player::Player,
+};
+
+mod common;
+use common::*;
+
+// start a co-op game, with aliens not dropping any bullets
+fn co_op() -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    start(&mut app, GameStateEvent::PressPlayCoOp);
+    hold_fire(&mut app);
+    app
+}
+
+fn players(app: &mut App) -> Vec<usize> {
+    let world = app.world_mut();
+    let mut players: Vec<usize> = world
+        .query::<&Player>()
+        .iter(world)
+        .map(|player| player.0)
+        .collect();
+    players.sort();
+    players
+}
+
+// drop an alien bullet right on the player
+fn hit(app: &mut App, player: usize) {
+    let world = app.world_mut();
+    let transform = *world
+        .query::<(&Player, &Transform)>()
+        .iter(world)
+        .find(|(p, _)| p.0 == player)
+        .unwrap()
+        .1;
+    world.spawn((AlienBullet, transform));
+}
+
+#[test]
+fn co_op_spawns_two_players() {
+    let mut app = co_op();
+    assert_eq!(players(&mut app), [0, 1]);
+
+    let lives = GameConfig::default().nr_lives;
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.nr_players, 2);
+    assert_eq!(store.lives, 2 * lives);
+    assert!(store.players.iter().all(|player| player.lives == lives));
+}
+
+#[test]
+fn kill_scores_for_the_shooting_player() {
+    let mut app = co_op();
+
+    // clear the line of fire, the second player below the bottom left alien
+    despawn_all::<With<Bunker>>(&mut app);
+    let world = app.world_mut();
+    let target = world
+        .query_filtered::<&Transform, With<Alien>>()
+        .iter(world)
+        .min_by(|a, b| {
+            (a.translation.x, a.translation.y)
+                .partial_cmp(&(b.translation.x, b.translation.y))
+                .unwrap()
+        })
+        .unwrap()
+        .translation;
+    for (player, mut transform) in world.query::<(&Player, &mut Transform)>().iter_mut(world) {
+        if player.0 == 1 {
+            transform.translation.x = target.x;
+        }
+    }
+
+    app.world_mut().send_event(FireLazerEvent(1));
+    run_until(&mut app, |app| {
+        app.world().resource::<Store>().aliens_killed > 0
+    });
+
+    let store = app.world().resource::<Store>();
+    let score = GameConfig::default().score_alien;
+    assert_eq!(store.players[0].score, 0);
+    assert_eq!(store.players[1].score, score);
+    assert_eq!(store.score, score);
+}
+
+#[test]
+fn hit_costs_the_players_life() {
+    let mut app = co_op();
+    hit(&mut app, 1);
+    run_until(&mut app, |app| state(app) != GameState::Play);
+    assert_eq!(state(&app), GameState::PlayerSpawn);
+
+    let lives = GameConfig::default().nr_lives;
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.players[0].lives, lives);
+    assert_eq!(store.players[1].lives, lives - 1);
+    assert_eq!(store.lives, 2 * lives - 1);
+}
+
+#[test]
+fn out_of_lives_the_other_plays_on() {
+    let mut app = co_op();
+    app.world_mut().resource_mut::<Store>().players[1].lives = 1;
+    hit(&mut app, 1);
+    run_until(&mut app, |app| players(app).len() == 1);
+
+    assert_eq!(players(&mut app), [0]);
+    assert_eq!(state(&app), GameState::Play);
+    assert_eq!(app.world().resource::<Store>().players[1].lives, 0);
+}
+
+#[test]
+fn game_over_enters_a_name_per_player() {
+    let mut app = co_op();
+    let mut store = app.world_mut().resource_mut::<Store>();
+    store.lives = 2;
+    store.players[0].lives = 1;
+    store.players[1].lives = 1;
+    // below the first extra life
+    store.players[0].score = 60;
+    store.players[1].score = 90;
+    store.score = 150;
+    hit(&mut app, 1);
+    run_until(&mut app, |app| players(app).len() == 1);
+    hit(&mut app, 0);
+    run_until(&mut app, |app| state(app) == GameState::EnterName);
+
+    // "AAA" for the first player, then "BAA" for the second
+    assert_eq!(app.world().resource::<NameEntry>().player, Some(0));
+    for event in [
+        NameEntryEvent::Select,
+        NameEntryEvent::Select,
+        NameEntryEvent::Select,
+        NameEntryEvent::Next,
+        NameEntryEvent::Select,
+        NameEntryEvent::Select,
+        NameEntryEvent::Select,
+    ] {
+        app.world_mut().send_event(event);
+        app.update();
+        if app.world().resource::<NameEntry>().player == Some(1) {
+            assert_eq!(state(&app), GameState::EnterName);
+        }
+    }
+    run_until(&mut app, |app| state(app) == GameState::LeaderBoard);
+
+    let entries = app.world().resource::<LeaderBoard>().entries();
+    let entries: Vec<(&str, u32)> = entries
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry.score))
+        .collect();
+    assert_eq!(entries, [("BAA", 90), ("AAA", 60)]);
+}
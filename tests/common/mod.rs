@@ -0,0 +1,71 @@
+//! Helpers shared by the integration tests, driving a `headless::app`
+#![allow(dead_code)] // each test file uses some of them
+
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use bevy_space::{
+    action::{Action, ActionEvent},
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    pause::PauseState,
+};
+
+pub const MAX_FRAMES: usize = 10_000;
+
+pub fn state(app: &App) -> GameState {
+    app.world().resource::<State<GameState>>().get().clone()
+}
+
+pub fn pause_state(app: &App) -> PauseState {
+    app.world().resource::<State<PauseState>>().get().clone()
+}
+
+// update until the predicate holds, panics after MAX_FRAMES
+pub fn run_until(app: &mut App, mut predicate: impl FnMut(&mut App) -> bool) {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        if predicate(app) {
+            return;
+        }
+    }
+    panic!("condition not reached in {} frames", MAX_FRAMES);
+}
+
+// the first player pressing the action for a frame
+pub fn press(app: &mut App, action: Action) {
+    app.world_mut()
+        .send_event(ActionEvent::button(0, action, true));
+    app.update();
+}
+
+// start a game by the event (one of the press play events), until in play
+pub fn start(app: &mut App, event: GameStateEvent) {
+    app.update();
+    app.world_mut().send_event(event);
+    run_until(app, |app| state(app) == GameState::Play);
+}
+
+// a headless single player game, in play
+pub fn play() -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    start(&mut app, GameStateEvent::PressPlay);
+    app
+}
+
+// aliens not dropping any bullets
+pub fn hold_fire(app: &mut App) {
+    app.world_mut().resource_mut::<Store>().bullet_interval = 1.0e6;
+}
+
+pub fn count<F: QueryFilter>(app: &mut App) -> usize {
+    let world = app.world_mut();
+    world.query_filtered::<(), F>().iter(world).count()
+}
+
+// e.g., `despawn_all::<With<Bunker>>(&mut app)` to clear the line of fire
+pub fn despawn_all<F: QueryFilter>(app: &mut App) {
+    let world = app.world_mut();
+    let entities: Vec<Entity> = world.query_filtered::<Entity, F>().iter(world).collect();
+    for entity in entities {
+        world.despawn(entity);
+    }
+}
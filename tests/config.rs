@@ -0,0 +1,80 @@
+use bevy_space::{
+    config::GameConfig,
+    difficulty::Difficulty,
+    game_state::{GameStateEvent, Store},
+    headless,
+};
+
+mod common;
+use common::*;
+
+fn set(name: &str, value: &str) -> (String, String) {
+    (name.to_string(), value.to_string())
+}
+
+#[test]
+fn defaults_and_overrides() {
+    assert_eq!(GameConfig::parse("()", &[]).unwrap(), GameConfig::default());
+
+    // left out settings take their default, the overrides apply in order
+    let config = GameConfig::parse(
+        "(nr_lives: 5, player_speed: 400.0)",
+        &[set("player_speed", "300"), set("player_speed", "350.0")],
+    )
+    .unwrap();
+    assert_eq!(config.nr_lives, 5);
+    assert_eq!(config.player_speed, 350.0);
+    assert_eq!(config.lazer_speed, GameConfig::default().lazer_speed);
+
+    // read by the type of the setting, e.g., an enum
+    let config = GameConfig::parse(
+        "(nr_lives: 5)",
+        &[
+            set("difficulty", "Hard"),
+            set("adaptive_difficulty", "true"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(config.difficulty, Difficulty::Hard);
+    assert!(config.adaptive_difficulty);
+    assert_eq!(config.nr_lives, 5);
+}
+
+#[test]
+fn difficulty_from_file_and_override() {
+    let path = std::env::temp_dir().join(format!("bevy_space_config_{}.ron", std::process::id()));
+    std::fs::write(&path, "(difficulty: Arcade, adaptive_difficulty: true)").unwrap();
+    let config = GameConfig::load(Some(&path), &[]).unwrap();
+    assert_eq!(config.difficulty, Difficulty::Arcade);
+    assert!(config.adaptive_difficulty);
+
+    // as `--set difficulty=Hard`, the command line taking precedence
+    let config = GameConfig::load(Some(&path), &[set("difficulty", "Hard")]).unwrap();
+    assert_eq!(config.difficulty, Difficulty::Hard);
+    std::fs::remove_file(&path).unwrap();
+
+    let config = GameConfig::load(None, &[set("difficulty", "Easy")]).unwrap();
+    assert_eq!(config.difficulty, Difficulty::Easy);
+}
+
+#[test]
+fn invalid_config() {
+    assert!(GameConfig::parse("(nr_lifes: 5)", &[]).is_err());
+    assert!(GameConfig::parse("(nr_lives: 0)", &[]).is_err());
+    assert!(GameConfig::parse("(player_speed: -1.0)", &[]).is_err());
+    assert!(GameConfig::parse("()", &[set("missile_probability", "2.0")]).is_err());
+    assert!(GameConfig::parse("()", &[set("nr_lives", "many")]).is_err());
+    assert!(GameConfig::parse("()", &[set("nr_lifes", "5")]).is_err());
+    assert!(GameConfig::parse("()", &[set("difficulty", "Impossible")]).is_err());
+    // a single value, not setting others along
+    assert!(GameConfig::parse("()", &[set("nr_lives", "3, max_lazers: 9")]).is_err());
+    assert!(GameConfig::parse("[1, 2]", &[]).is_err());
+}
+
+#[test]
+fn config_sets_lives() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.world_mut().resource_mut::<GameConfig>().nr_lives = 5;
+    start(&mut app, GameStateEvent::PressPlay);
+    assert_eq!(app.world().resource::<Store>().lives, 5);
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_space::{
+    config::GameConfig,
+    difficulty::{AdaptiveDifficulty, Difficulty},
+    game_state::{GameStateEvent, Store},
+    headless,
+    wave::Waves,
+};
+
+mod common;
+use common::*;
+
+fn play_with(config: GameConfig) -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.insert_resource(config);
+    start(&mut app, GameStateEvent::PressPlay);
+    app
+}
+
+#[test]
+fn presets_scale_the_store() {
+    let wave = Waves::default().get(1);
+    let normal = play_with(GameConfig::default());
+    let hard = play_with(GameConfig {
+        difficulty: Difficulty::Hard,
+        ..default()
+    });
+    let normal = normal.world().resource::<Store>();
+    let hard = hard.world().resource::<Store>();
+
+    assert_eq!(normal.alien_speed, wave.speed);
+    assert_eq!(normal.bullet_interval, wave.bullet_interval);
+    assert_eq!(
+        normal.players[0].score_new_life,
+        GameConfig::default().score_new_life
+    );
+    assert!(hard.alien_speed > normal.alien_speed);
+    assert!(hard.bullet_interval < normal.bullet_interval);
+    assert!(hard.bullet_speed > normal.bullet_speed);
+    assert!(hard.players[0].score_new_life > normal.players[0].score_new_life);
+}
+
+#[test]
+fn adaptive_difficulty_eases_on_poor_accuracy() {
+    let mut app = play_with(GameConfig {
+        adaptive_difficulty: true,
+        ..default()
+    });
+    let alien_speed = app.world().resource::<Store>().alien_speed;
+
+    // all shots missed
+    app.world_mut().resource_mut::<Store>().shots_fired = 10;
+    run_until(&mut app, |app| {
+        app.world().resource::<AdaptiveDifficulty>().level() < 1.0
+    });
+    assert!(app.world().resource::<Store>().alien_speed < alien_speed);
+
+    // all shots hit
+    let mut store = app.world_mut().resource_mut::<Store>();
+    store.shots_fired += 10;
+    store.shots_hit += 10;
+    run_until(&mut app, |app| {
+        app.world().resource::<AdaptiveDifficulty>().level() == 1.0
+    });
+}
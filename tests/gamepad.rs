@@ -0,0 +1,132 @@
+use bevy::{
+    input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+        InputPlugin,
+    },
+    prelude::*,
+};
+use bevy_space::{
+    game_state::{GameState, GameStateEvent, Store},
+    gamepad::{GamepadAssignment, GamepadPlugin},
+    headless,
+    pause::PauseState,
+};
+
+mod common;
+use common::*;
+
+fn connection(gamepad: Gamepad, connection: GamepadConnection) -> GamepadConnectionEvent {
+    GamepadConnectionEvent {
+        gamepad,
+        connection,
+    }
+}
+
+fn paused(app: &App) -> bool {
+    app.world().resource::<Time<Virtual>>().is_paused()
+}
+
+#[test]
+fn gamepads_join_in_turn() {
+    let (first, second, third) = (Gamepad::new(3), Gamepad::new(1), Gamepad::new(2));
+    let mut assignment = GamepadAssignment::default();
+    assert_eq!(assignment.join(first), Some(0));
+    assert_eq!(assignment.join(first), Some(0));
+    assert_eq!(assignment.join(second), Some(1));
+    assert_eq!(assignment.join(third), None);
+
+    // the first player's place is taken by the next to join
+    assert_eq!(assignment.leave(first), Some(0));
+    assert_eq!(assignment.leave(first), None);
+    assert_eq!(assignment.player(second), Some(1));
+    assert_eq!(assignment.join(third), Some(0));
+    assert_eq!(assignment.gamepad(0), Some(third));
+}
+
+#[test]
+fn keyboard_plays_the_player_without_gamepad() {
+    let mut assignment = GamepadAssignment::default();
+    let mut store = Store::default();
+    assignment.join(Gamepad::new(0));
+    assert_eq!(assignment.keyboard_player(&store), 0);
+
+    store.nr_players = 2;
+    assert_eq!(assignment.keyboard_player(&store), 1);
+
+    store.alternating = true;
+    store.turn = 1;
+    assert_eq!(assignment.keyboard_player(&store), 1);
+}
+
+// an app with the gamepads joined in order, in play by the event
+fn play_with_gamepads(gamepads: &[Gamepad], event: GameStateEvent) -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.add_plugins((InputPlugin, GamepadPlugin));
+    app.update();
+
+    for gamepad in gamepads {
+        let info = GamepadInfo {
+            name: "pad".to_string(),
+        };
+        app.world_mut()
+            .send_event(connection(*gamepad, GamepadConnection::Connected(info)));
+        app.world_mut()
+            .resource_mut::<GamepadAssignment>()
+            .join(*gamepad);
+    }
+    app.world_mut().send_event(event);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+    assert!(!paused(&app));
+    app
+}
+
+#[test]
+fn disconnect_in_game_pauses() {
+    let gamepad = Gamepad::new(0);
+    let mut app = play_with_gamepads(&[gamepad], GameStateEvent::PressPlay);
+
+    app.world_mut()
+        .send_event(connection(gamepad, GamepadConnection::Disconnected));
+    run_until(&mut app, |app| paused(app));
+    assert_eq!(
+        app.world().resource::<State<PauseState>>().get(),
+        &PauseState::Paused
+    );
+    let assignment = app.world().resource::<GamepadAssignment>();
+    assert_eq!(assignment.paused(), Some(0));
+    assert_eq!(assignment.gamepad(0), None);
+
+    // the game is on hold
+    let elapsed = app.world().resource::<Time<Virtual>>().elapsed();
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<Time<Virtual>>().elapsed(), elapsed);
+    assert_eq!(state(&app), GameState::Play);
+}
+
+#[test]
+fn disconnect_off_screen_plays_on() {
+    let (first, second) = (Gamepad::new(0), Gamepad::new(1));
+    let mut app = play_with_gamepads(&[first, second], GameStateEvent::PressPlayAlternating);
+    assert_eq!(app.world().resource::<Store>().turn, 0);
+
+    // the second player waiting for the turn
+    app.world_mut()
+        .send_event(connection(second, GamepadConnection::Disconnected));
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(!paused(&app));
+    assert_eq!(pause_state(&app), PauseState::Running);
+    assert_eq!(app.world().resource::<GamepadAssignment>().gamepad(1), None);
+
+    // the player on screen
+    app.world_mut()
+        .send_event(connection(first, GamepadConnection::Disconnected));
+    run_until(&mut app, |app| paused(app));
+    assert_eq!(
+        app.world().resource::<GamepadAssignment>().paused(),
+        Some(0)
+    );
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet},
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    lazer::FireLazerEvent,
+    player::Player,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn starts_in_insert_coin() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    assert_eq!(state(&app), GameState::InsertCoin);
+}
+
+#[test]
+fn press_play_spawns_player() {
+    let app = play();
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.lives, GameConfig::default().nr_lives);
+    assert_eq!(store.score, 0);
+}
+
+#[test]
+fn fire_at_column_0_kills_bottom_alien() {
+    let mut app = play();
+    hold_fire(&mut app);
+    let aliens = count::<With<Alien>>(&mut app);
+    assert_eq!(aliens, ALIENS_TOTAL as usize);
+
+    // clear the line of fire
+    despawn_all::<With<Bunker>>(&mut app);
+    let world = app.world_mut();
+
+    // place the player below the bottom alien of the left most column
+    let target = world
+        .query_filtered::<&Transform, With<Alien>>()
+        .iter(world)
+        .min_by(|a, b| {
+            (a.translation.x, a.translation.y)
+                .partial_cmp(&(b.translation.x, b.translation.y))
+                .unwrap()
+        })
+        .unwrap()
+        .translation;
+    world
+        .query_filtered::<&mut Transform, With<Player>>()
+        .single_mut(world)
+        .translation
+        .x = target.x;
+
+    app.world_mut().send_event(FireLazerEvent(0));
+    run_until(&mut app, |app| count::<With<Alien>>(app) < aliens);
+
+    assert_eq!(count::<With<Alien>>(&mut app), aliens - 1);
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.score, GameConfig::default().score_alien);
+    assert_eq!(store.aliens_killed, 1);
+
+    // the bottom alien of the column was hit
+    let world = app.world_mut();
+    assert!(!world
+        .query_filtered::<&Transform, With<Alien>>()
+        .iter(world)
+        .any(|t| t.translation.y <= target.y && (t.translation.x - target.x).abs() < 1.0));
+}
+
+#[test]
+fn loosing_all_lives_is_game_over() {
+    let mut app = play();
+    hold_fire(&mut app);
+
+    for lives in (0..GameConfig::default().nr_lives).rev() {
+        // drop an alien bullet right on the player
+        let world = app.world_mut();
+        let transform = *world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(world);
+        world.spawn((AlienBullet, transform));
+
+        run_until(&mut app, |app| state(app) != GameState::Play);
+        assert_eq!(app.world().resource::<Store>().lives, lives);
+
+        if lives > 0 {
+            assert_eq!(state(&app), GameState::PlayerSpawn);
+            run_until(&mut app, |app| state(app) == GameState::Play);
+        }
+    }
+    assert_eq!(state(&app), GameState::GameOver);
+
+    run_until(&mut app, |app| state(app) == GameState::InsertCoin);
+}
+
+// alien bullets and lives after playing for a while, without any player input
+fn simulate(seed: u64) -> (Vec<Vec3>, u8) {
+    let mut app = headless::app_with_seed(headless::HEADLESS_TICK_RATE, seed);
+    app.update();
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    for _ in 0..1200 {
+        app.update();
+    }
+    let world = app.world_mut();
+    let bullets = world
+        .query_filtered::<&Transform, With<AlienBullet>>()
+        .iter(world)
+        .map(|t| t.translation)
+        .collect();
+    (bullets, world.resource::<Store>().lives)
+}
+
+#[test]
+fn same_seed_same_outcome() {
+    let (bullets, lives) = simulate(42);
+    assert!(!bullets.is_empty());
+    assert_eq!((bullets, lives), simulate(42));
+}
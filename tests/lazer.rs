@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::Alien,
+    common::*,
+    config::GameConfig,
+    game_state::Store,
+    headless,
+    lazer::{FireLazerEvent, Lazer},
+    player::Player,
+    weapon::Weapon,
+};
+
+mod common;
+use common::*;
+
+fn in_flight(app: &mut App) -> usize {
+    count::<With<Lazer>>(app)
+}
+
+// play with a clear line of fire, in between bunkers without aliens
+fn play_with(weapon: Weapon) -> App {
+    let mut app = play();
+    despawn_all::<With<Alien>>(&mut app);
+
+    let world = app.world_mut();
+    let (mut transform, mut player_weapon) = world
+        .query_filtered::<(&mut Transform, &mut Weapon), With<Player>>()
+        .single_mut(world);
+    transform.translation.x = BUNKER_SPACE;
+    *player_weapon = weapon;
+    app
+}
+
+#[test]
+fn lazer_despawned_out_of_scene() {
+    let mut app = play_with(Weapon::Single);
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    assert_eq!(in_flight(&mut app), 1);
+
+    run_until(&mut app, |app| in_flight(app) == 0);
+    assert_eq!(app.world().resource::<Store>().shots_fired, 1);
+}
+
+#[test]
+fn lazers_in_flight_limited() {
+    let mut app = play_with(Weapon::Rapid);
+    app.world_mut().resource_mut::<GameConfig>().max_lazers = 2;
+
+    // keep firing, a new shot once the last is clear of the cannon
+    let mut most = 0;
+    for _ in 0..headless::HEADLESS_TICK_RATE as usize {
+        app.world_mut().send_event(FireLazerEvent(0));
+        app.update();
+        most = most.max(in_flight(&mut app));
+    }
+    assert_eq!(most, 2);
+}
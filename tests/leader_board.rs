@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::AlienBullet,
+    common::*,
+    game_state::{GameState, Store},
+    leader_board::{Entry, LeaderBoard, NameEntryEvent},
+    player::Player,
+};
+
+mod common;
+use common::*;
+
+fn entry(name: &str, score: u32) -> Entry {
+    Entry {
+        name: name.to_string(),
+        score,
+        wave: 1,
+        date: 0,
+    }
+}
+
+#[test]
+fn insert_keeps_highest_scores() {
+    let mut leader_board = LeaderBoard::default();
+    for score in 1..=LEADER_BOARD_ENTRIES as u32 + 2 {
+        leader_board.insert(entry("AAA", score * 10));
+    }
+    leader_board.insert(entry("BBB", 50));
+
+    let scores: Vec<u32> = leader_board.entries().iter().map(|e| e.score).collect();
+    assert_eq!(scores.len(), LEADER_BOARD_ENTRIES);
+    assert_eq!(scores[0], (LEADER_BOARD_ENTRIES as u32 + 2) * 10);
+    assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+
+    // a tie is placed after the earlier entry
+    let index = leader_board
+        .entries()
+        .iter()
+        .position(|e| e.name == "BBB")
+        .unwrap();
+    assert_eq!(leader_board.entries()[index - 1].score, 50);
+}
+
+#[test]
+fn qualifies() {
+    let mut leader_board = LeaderBoard::default();
+    assert!(!leader_board.qualifies(0));
+    assert!(leader_board.qualifies(1));
+
+    for _ in 0..LEADER_BOARD_ENTRIES {
+        leader_board.insert(entry("AAA", 100));
+    }
+    assert!(!leader_board.qualifies(100));
+    assert!(leader_board.qualifies(101));
+}
+
+#[test]
+fn parse_skips_malformed_lines() {
+    let entries = LeaderBoard::parse("AAA\t10\t1\t0\nnonsense\nBBB\t20\t2\t86400\n");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "BBB");
+    assert_eq!(entries[0].date_string(), "1970-01-02");
+    assert_eq!(entries[1].name, "AAA");
+}
+
+#[test]
+fn date_string() {
+    let mut entry = entry("AAA", 0);
+    entry.date = 1_709_208_000; // leap day
+    assert_eq!(entry.date_string(), "2024-02-29");
+}
+
+#[test]
+fn game_over_enters_name() {
+    let mut app = play();
+
+    // last life lost, with a score on the board
+    let world = app.world_mut();
+    let mut store = world.resource_mut::<Store>();
+    store.lives = 1;
+    store.score = 90; // below the first extra life
+    store.players[0].score = 90;
+    let transform = *world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world);
+    world.spawn((AlienBullet, transform));
+    run_until(&mut app, |app| state(app) == GameState::EnterName);
+
+    // "ABC", picking the next letter for each but the first
+    for event in [
+        NameEntryEvent::Select,
+        NameEntryEvent::Next,
+        NameEntryEvent::Select,
+        NameEntryEvent::Next,
+        NameEntryEvent::Next,
+        NameEntryEvent::Select,
+    ] {
+        app.world_mut().send_event(event);
+        app.update();
+    }
+    run_until(&mut app, |app| state(app) == GameState::LeaderBoard);
+
+    let entries = app.world().resource::<LeaderBoard>().entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "ABC");
+    assert_eq!(entries[0].score, 90);
+}
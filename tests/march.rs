@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy_space::{
+    audio::PlaySoundEvent,
+    common::*,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    march::interval,
+    wave::CurrentWave,
+};
+
+mod common;
+use common::*;
+
+// the notes played so far, and the game state each was played in
+#[derive(Resource, Default)]
+struct Notes(Vec<(PlaySoundEvent, GameState)>);
+
+fn collect_system(
+    mut play_sound_er: EventReader<PlaySoundEvent>,
+    state: Res<State<GameState>>,
+    mut notes: ResMut<Notes>,
+) {
+    for sound in play_sound_er.read() {
+        if PlaySoundEvent::MARCH.contains(sound) {
+            notes.0.push((*sound, state.get().clone()));
+        }
+    }
+}
+
+fn notes(app: &App) -> usize {
+    app.world().resource::<Notes>().0.len()
+}
+
+fn playing() -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.init_resource::<Notes>()
+        .add_systems(Last, collect_system);
+    start(&mut app, GameStateEvent::PressPlay);
+    app
+}
+
+// frames from one note to the next
+fn beat_frames(app: &mut App) -> usize {
+    let start = notes(app);
+    run_until(app, |app| notes(app) > start);
+    let mut frames = 0;
+    run_until(app, |app| {
+        frames += 1;
+        notes(app) > start + 1
+    });
+    frames
+}
+
+#[test]
+fn interval_follows_speed_and_aliens() {
+    assert_eq!(interval(ALIENS_SPEED_START, 55, 55), MARCH_INTERVAL_MAX);
+    assert!(interval(ALIENS_SPEED_MAX, 55, 55) < interval(ALIENS_SPEED_START, 55, 55));
+    assert!(interval(ALIENS_SPEED_MAX, 10, 55) < interval(ALIENS_SPEED_MAX, 55, 55));
+    assert_eq!(interval(ALIENS_SPEED_MAX * 10.0, 1, 55), MARCH_INTERVAL_MIN);
+    assert_eq!(
+        interval(0.0, 0, 0),
+        MARCH_INTERVAL_MAX * MARCH_INTERVAL_LAST
+    );
+}
+
+#[test]
+fn beat_cycles_through_the_notes_in_play() {
+    let mut app = playing();
+    run_until(&mut app, |app| notes(app) == 5);
+    let notes = &app.world().resource::<Notes>().0;
+    assert!(notes.iter().all(|(_, state)| *state == GameState::Play));
+    let sounds: Vec<PlaySoundEvent> = notes.iter().map(|(sound, _)| *sound).collect();
+    assert_eq!(
+        sounds,
+        [
+            PlaySoundEvent::March1,
+            PlaySoundEvent::March2,
+            PlaySoundEvent::March3,
+            PlaySoundEvent::March4,
+            PlaySoundEvent::March1,
+        ]
+    );
+}
+
+#[test]
+fn beat_quickens_as_the_formation_thins_out() {
+    let mut app = playing();
+    let full = beat_frames(&mut app);
+
+    let aliens_total = app.world().resource::<CurrentWave>().aliens_total();
+    app.world_mut().resource_mut::<Store>().aliens_killed = aliens_total - 1;
+    let last = beat_frames(&mut app);
+    assert!(
+        last < full / 2,
+        "{} frames, {} with the full formation",
+        last,
+        full
+    );
+}
+
+#[test]
+fn beat_rests_over_a_new_wave() {
+    let mut app = playing();
+    run_until(&mut app, |app| notes(app) > 0);
+
+    app.world_mut().send_event(GameStateEvent::NewWave);
+    run_until(&mut app, |app| state(app) == GameState::NewWave);
+    let rested = notes(&app);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+    assert_eq!(notes(&app), rested);
+
+    // starting over on the first note
+    run_until(&mut app, |app| notes(app) > rested);
+    let notes = &app.world().resource::<Notes>().0;
+    assert_eq!(notes[rested].0, PlaySoundEvent::March1);
+    assert!(notes.iter().all(|(_, state)| *state == GameState::Play));
+}
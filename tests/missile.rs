@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienKind, HomingMissile},
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    game_state::{GameState, Store},
+    headless,
+    lazer::FireLazerEvent,
+    player::Player,
+    wave::{CurrentWave, Waves},
+};
+
+mod common;
+use common::*;
+
+fn missile(app: &mut App) -> Option<(Entity, Vec3)> {
+    let world = app.world_mut();
+    world
+        .query_filtered::<(Entity, &Transform), With<HomingMissile>>()
+        .iter(world)
+        .next()
+        .map(|(entity, t)| (entity, t.translation))
+}
+
+fn exists(app: &App, entity: Entity) -> bool {
+    app.world().get_entity(entity).is_some()
+}
+
+fn move_player(app: &mut App, x: f32) {
+    let world = app.world_mut();
+    world
+        .query_filtered::<&mut Transform, With<Player>>()
+        .single_mut(world)
+        .translation
+        .x = x;
+}
+
+// play a wave firing missiles, without bullets nor bunkers
+fn play_missile_wave() -> App {
+    let mut app = play();
+    hold_fire(&mut app);
+    despawn_all::<With<Bunker>>(&mut app);
+
+    let world = app.world_mut();
+    let wave = world.resource::<Waves>().get(MISSILE_WAVE);
+    world.resource_mut::<CurrentWave>().0 = wave;
+    world.resource_mut::<Store>().wave = MISSILE_WAVE;
+
+    run_until(&mut app, |app| missile(app).is_some());
+    app
+}
+
+#[test]
+fn missile_fired_from_any_row() {
+    let mut app = play();
+    hold_fire(&mut app);
+    despawn_all::<With<Bunker>>(&mut app);
+    let world = app.world_mut();
+    let wave = world.resource::<Waves>().get(MISSILE_WAVE);
+    world.resource_mut::<CurrentWave>().0 = wave;
+    world.resource_mut::<Store>().wave = MISSILE_WAVE;
+
+    // the squids of the top row turned crabs, a single squid in the bottom row
+    let mut aliens: Vec<(Mut<AlienKind>, &Transform)> = world
+        .query_filtered::<(&mut AlienKind, &Transform), With<Alien>>()
+        .iter_mut(world)
+        .collect();
+    let y_min = aliens
+        .iter()
+        .fold(f32::MAX, |y_min, (_, t)| y_min.min(t.translation.y));
+    for (kind, _) in &mut aliens {
+        if **kind == AlienKind::Squid {
+            **kind = AlienKind::Crab;
+        }
+    }
+    let (kind, bottom) = aliens
+        .iter_mut()
+        .find(|(_, t)| t.translation.y == y_min)
+        .unwrap();
+    **kind = AlienKind::Squid;
+    let bottom = bottom.translation;
+
+    run_until(&mut app, |app| missile(app).is_some());
+    let (_, start) = missile(&mut app).unwrap();
+    assert!((start.y - bottom.y).abs() < ALIENS_SPACE);
+}
+
+#[test]
+fn no_missiles_in_early_waves() {
+    let mut app = play();
+    for _ in 0..(10.0 * GameConfig::default().missile_interval) as usize * 60 {
+        app.update();
+        assert!(missile(&mut app).is_none());
+    }
+}
+
+#[test]
+fn missile_homes_in_on_player() {
+    let mut app = play_missile_wave();
+    let (entity, start) = missile(&mut app).unwrap();
+    move_player(&mut app, start.x + 150.0);
+
+    let lives = app.world().resource::<Store>().lives;
+    run_until(&mut app, |app| state(app) != GameState::Play);
+    assert_eq!(state(&app), GameState::PlayerSpawn);
+    assert_eq!(app.world().resource::<Store>().lives, lives - 1);
+    assert!(!exists(&app, entity));
+}
+
+#[test]
+fn missile_self_destructs() {
+    let mut app = play_missile_wave();
+    // out of reach, at the far side
+    let (entity, start) = missile(&mut app).unwrap();
+    move_player(&mut app, -start.x.signum() * SCENE_WIDTH);
+
+    let frames =
+        (GameConfig::default().missile_lifetime as f64 * headless::HEADLESS_TICK_RATE) as usize;
+    for _ in 0..frames - 10 {
+        app.update();
+    }
+    assert!(exists(&app, entity));
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(!exists(&app, entity));
+    assert_eq!(state(&app), GameState::Play);
+}
+
+#[test]
+fn lazer_shoots_down_missile() {
+    let mut app = play_missile_wave();
+    let (entity, start) = missile(&mut app).unwrap();
+    move_player(&mut app, start.x);
+
+    // fire once the missile is clear of the formation
+    run_until(&mut app, |app| {
+        let world = app.world_mut();
+        let y_min = world
+            .query_filtered::<&Transform, With<Alien>>()
+            .iter(world)
+            .fold(f32::MAX, |y_min, t| y_min.min(t.translation.y));
+        missile(app).unwrap().1.y < y_min - ALIEN_SIZE.y
+    });
+    let score = app.world().resource::<Store>().score;
+    app.world_mut().send_event(FireLazerEvent(0));
+    run_until(&mut app, |app| !exists(app, entity));
+    assert_eq!(state(&app), GameState::Play);
+    assert_eq!(
+        app.world().resource::<Store>().score,
+        score + GameConfig::default().score_missile
+    );
+}
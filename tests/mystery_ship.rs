@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::Alien,
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    game_state::Store,
+    headless,
+    lazer::FireLazerEvent,
+    mystery_ship::{self, MysteryShip},
+    player::Player,
+};
+
+mod common;
+use common::*;
+
+fn mystery_ship(app: &mut App) -> Option<Transform> {
+    let world = app.world_mut();
+    world
+        .query_filtered::<&Transform, With<MysteryShip>>()
+        .iter(world)
+        .next()
+        .copied()
+}
+
+#[test]
+fn score_by_shot_count() {
+    assert_eq!(mystery_ship::score(1), 50);
+    assert_eq!(mystery_ship::score(23), 300);
+    assert_eq!(mystery_ship::score(38), 300);
+    assert_eq!(mystery_ship::score(53), 300);
+}
+
+#[test]
+fn mystery_ship_appears_and_is_hit() {
+    let mut app = play();
+
+    // clear the line of fire, the aliens are not counted as killed
+    despawn_all::<Or<(With<Alien>, With<Bunker>)>>(&mut app);
+
+    // appears after the interval, entering from the right on an even shot count
+    let frames = (MYSTERY_SHIP_INTERVAL as f64 * headless::HEADLESS_TICK_RATE) as usize;
+    for _ in 0..frames - 10 {
+        app.update();
+    }
+    assert!(mystery_ship(&mut app).is_none());
+    run_until(&mut app, |app| mystery_ship(app).is_some());
+    let transform = mystery_ship(&mut app).unwrap();
+    assert!(transform.translation.x > SCENE_WIDTH);
+
+    // fire when the ship is right above, the lazer takes about
+    // (MYSTERY_SHIP_Y - player y) / lazer_speed to reach it
+    let world = app.world_mut();
+    let player_y = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation
+        .y;
+    let config = GameConfig::default();
+    let lead = (MYSTERY_SHIP_Y - player_y) / config.lazer_speed * config.mystery_ship_speed;
+    run_until(&mut app, |app| {
+        mystery_ship(app).unwrap().translation.x < lead
+    });
+    let score = app.world().resource::<Store>().score;
+    app.world_mut().send_event(FireLazerEvent(0));
+    run_until(&mut app, |app| mystery_ship(app).is_none());
+
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.shots_fired, 1);
+    assert_eq!(store.score, score + mystery_ship::score(1));
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::AlienBullet,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    leader_board::Entry,
+    online::{
+        HttpBackend, MockBackend, OnlineBackend, OnlineLeaderBoard, OnlinePlugin, Submission,
+        SubmissionQueue,
+    },
+    player::Player,
+};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    sync::Arc,
+    thread,
+};
+
+mod common;
+use common::*;
+
+fn submission(name: &str, score: u32) -> Submission {
+    Submission {
+        entry: Entry {
+            name: name.to_string(),
+            score,
+            wave: 2,
+            date: 1_709_208_000,
+        },
+        seed: 42,
+        tick_rate: 60.0,
+        version: "0.1.0".to_string(),
+    }
+}
+
+fn app(mock: &Arc<MockBackend>, queue_path: Option<PathBuf>) -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.add_plugins(OnlinePlugin {
+        backend: mock.clone(),
+        queue_path,
+    });
+    app.update();
+    app
+}
+
+// play a game ending with the given score (below the first extra life),
+// not entering a name
+fn game_over(app: &mut App, score: u32) {
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    run_until(app, |app| state(app) == GameState::Play);
+
+    let world = app.world_mut();
+    let mut store = world.resource_mut::<Store>();
+    store.lives = 1;
+    store.score = score;
+    store.players[0].score = score;
+    let transform = *world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world);
+    world.spawn((AlienBullet, transform));
+    run_until(app, |app| state(app) == GameState::LeaderBoard);
+}
+
+#[test]
+fn submission_line() {
+    let submission = submission("ABC", 120);
+    assert_eq!(
+        Submission::from_line(&submission.to_line()),
+        Some(submission)
+    );
+    assert_eq!(Submission::from_line("ABC\t120\t2"), None);
+}
+
+#[test]
+fn game_over_submits_and_fetches() {
+    let mock = Arc::new(MockBackend::default());
+    mock.submit(&submission("XYZ", 500)).unwrap();
+    let mut app = app(&mock, None);
+
+    game_over(&mut app, 90);
+    run_until(&mut app, |app| {
+        app.world().resource::<OnlineLeaderBoard>().entries.len() == 2
+    });
+
+    let submissions = mock.submissions();
+    assert_eq!(submissions.len(), 2);
+    assert_eq!(submissions[1].entry.score, 90);
+    assert_eq!(submissions[1].entry.name, "AAA"); // name entry timed out
+    assert_eq!(submissions[1].seed, 0);
+
+    let entries = &app.world().resource::<OnlineLeaderBoard>().entries;
+    assert_eq!(entries[0].name, "XYZ");
+    assert_eq!(entries[1].score, 90);
+}
+
+#[test]
+fn co_op_submits_each_player() {
+    let mock = Arc::new(MockBackend::default());
+    let mut app = app(&mock, None);
+    app.world_mut().send_event(GameStateEvent::PressPlayCoOp);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+    hold_fire(&mut app);
+
+    // both out of their last life at once
+    let world = app.world_mut();
+    let mut store = world.resource_mut::<Store>();
+    store.lives = 2;
+    for (player, score) in [60, 90].into_iter().enumerate() {
+        store.players[player].lives = 1;
+        store.players[player].score = score;
+    }
+    store.score = 150;
+    let transforms: Vec<Transform> = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .copied()
+        .collect();
+    for transform in transforms {
+        world.spawn((AlienBullet, transform));
+    }
+    run_until(&mut app, |app| state(app) == GameState::LeaderBoard);
+    run_until(&mut app, |_| mock.submissions().len() == 2);
+
+    let mut scores: Vec<u32> = mock
+        .submissions()
+        .iter()
+        .map(|submission| submission.entry.score)
+        .collect();
+    scores.sort();
+    assert_eq!(scores, [60, 90]);
+}
+
+#[test]
+fn offline_submissions_are_queued() {
+    let queue_path = std::env::temp_dir().join(format!(
+        "bevy_space_online_queue_{}.txt",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&queue_path);
+
+    // played offline, the submission is kept in the queue
+    let mock = Arc::new(MockBackend::default());
+    mock.set_offline(true);
+    let mut app_offline = app(&mock, Some(queue_path.clone()));
+    game_over(&mut app_offline, 90);
+    for _ in 0..10 {
+        app_offline.update();
+    }
+    assert_eq!(
+        app_offline
+            .world()
+            .resource::<SubmissionQueue>()
+            .submissions()
+            .len(),
+        1
+    );
+    assert!(mock.submissions().is_empty());
+
+    // submitted on the next start, once online
+    mock.set_offline(false);
+    let mut app_online = app(&mock, Some(queue_path.clone()));
+    run_until(&mut app_online, |app| {
+        app.world()
+            .resource::<SubmissionQueue>()
+            .submissions()
+            .is_empty()
+    });
+    assert_eq!(mock.submissions().len(), 1);
+    assert_eq!(mock.submissions()[0].entry.score, 90);
+    assert_eq!(std::fs::read_to_string(&queue_path).unwrap(), "");
+    let _ = std::fs::remove_file(&queue_path);
+}
+
+// a local server, answering a single request with the given body
+fn serve(body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+            if line == "\r\n" {
+                break;
+            }
+            request += &line;
+        }
+        let mut content = vec![0; content_length];
+        reader.read_exact(&mut content).unwrap();
+        request += &String::from_utf8(content).unwrap();
+
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        request
+    });
+    (url, handle)
+}
+
+#[test]
+fn http_backend() {
+    let (url, server) = serve("");
+    let submission = submission("ABC", 120);
+    HttpBackend::new(url).submit(&submission).unwrap();
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /scores "));
+    assert!(request.ends_with(&submission.to_line()));
+
+    let (url, server) = serve("AAA\t10\t1\t0\nBBB\t20\t1\t0\n");
+    let entries = HttpBackend::new(url + "/").fetch(10).unwrap();
+    assert!(server.join().unwrap().starts_with("GET /scores?count=10 "));
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name, "BBB");
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_space::{
+    action::Action,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    pause::{PauseItem, PauseMenu, PauseState},
+};
+
+mod common;
+use common::*;
+
+fn elapsed(app: &App) -> std::time::Duration {
+    app.world().resource::<Time<Virtual>>().elapsed()
+}
+
+// playing, with the pause menu open
+fn paused(event: GameStateEvent) -> App {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    start(&mut app, event);
+    press(&mut app, Action::Pause);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Paused);
+    app
+}
+
+#[test]
+fn pause_freezes_the_game() {
+    let mut app = paused(GameStateEvent::PressPlay);
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+    let frozen = elapsed(&app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(elapsed(&app), frozen);
+    assert_eq!(state(&app), GameState::Play);
+
+    press(&mut app, Action::Pause);
+    run_until(&mut app, |app| elapsed(app) > frozen);
+    assert_eq!(pause_state(&app), PauseState::Running);
+}
+
+#[test]
+fn restart_keeps_the_players() {
+    let mut app = paused(GameStateEvent::PressPlayCoOp);
+    app.world_mut().resource_mut::<Store>().add_score(1, 100);
+
+    press(&mut app, Action::MoveRight);
+    assert_eq!(
+        app.world().resource::<PauseMenu>().item(),
+        PauseItem::Restart
+    );
+    press(&mut app, Action::Fire);
+    run_until(&mut app, |app| state(app) == GameState::Start);
+    assert_eq!(pause_state(&app), PauseState::Running);
+    let store = app.world().resource::<Store>();
+    assert_eq!(store.nr_players, 2);
+    assert_eq!(store.score, 0);
+}
+
+#[test]
+fn settings_return_to_the_pause_menu() {
+    let mut app = paused(GameStateEvent::PressPlay);
+    press(&mut app, Action::MoveRight);
+    press(&mut app, Action::MoveRight);
+    press(&mut app, Action::Fire);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Settings);
+
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Paused);
+    assert_eq!(
+        app.world().resource::<PauseMenu>().item(),
+        PauseItem::Settings
+    );
+    assert_eq!(state(&app), GameState::Play);
+}
+
+#[test]
+fn quit_exits() {
+    let mut app = paused(GameStateEvent::PressPlay);
+    press(&mut app, Action::MoveLeft);
+    assert_eq!(app.world().resource::<PauseMenu>().item(), PauseItem::Quit);
+    press(&mut app, Action::Fire);
+    assert_eq!(app.should_exit(), Some(AppExit::Success));
+}
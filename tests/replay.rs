@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet},
+    game_state::{GameStateEvent, Store},
+    headless,
+    lazer::FireLazerEvent,
+    player::{Player, PlayerEvent},
+    replay::{Replay, ReplayMode, ReplayRecorder, TickInput},
+};
+
+const FRAMES: usize = 1800;
+
+// score, lives, aliens left, alien bullets and player position
+fn outcome(app: &mut App) -> (u32, u8, usize, Vec<Vec3>, Vec3) {
+    let world = app.world_mut();
+    let aliens = world
+        .query_filtered::<(), With<Alien>>()
+        .iter(world)
+        .count();
+    let bullets = world
+        .query_filtered::<&Transform, With<AlienBullet>>()
+        .iter(world)
+        .map(|t| t.translation)
+        .collect();
+    let player = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation;
+    let store = world.resource::<Store>();
+    (store.score, store.lives, aliens, bullets, player)
+}
+
+#[test]
+fn encode_decode() {
+    let mut replay = Replay::new(42, 60.0);
+    replay.ticks.extend([TickInput::default(); 1000]);
+    replay.ticks.push(TickInput {
+        movement: [-0.2, 0.7],
+        fire: [true, false],
+        press_play: false,
+        press_play_co_op: false,
+        press_play_alternating: true,
+        info: true,
+    });
+    replay.ticks.extend([TickInput::default(); 1000]);
+
+    let bytes = replay.to_bytes();
+    // identical ticks are run-length encoded
+    assert!(bytes.len() < 100);
+    assert_eq!(Replay::from_bytes(&bytes).unwrap(), replay);
+
+    assert!(Replay::from_bytes(b"XXXX").is_err());
+    assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn replay_reproduces_session() {
+    let path = std::env::temp_dir().join("bevy_space_replay_test.bin");
+    let mut app = headless::app_with_replay(
+        headless::HEADLESS_TICK_RATE,
+        7,
+        ReplayMode::Record(path.clone()),
+    );
+    app.update();
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    for frame in 0..FRAMES {
+        let world = app.world_mut();
+        if frame % 40 == 0 {
+            world.send_event(FireLazerEvent(0));
+        }
+        match (frame / 200) % 3 {
+            0 => world.send_event(PlayerEvent(0, -1.0)),
+            1 => world.send_event(PlayerEvent(0, 0.5)),
+            _ => None,
+        };
+        app.update();
+    }
+    let recorded = outcome(&mut app);
+    assert!(recorded.0 > 0, "some aliens should be hit");
+
+    let replay = app.world().resource::<ReplayRecorder>().replay().clone();
+    replay.save(&path).unwrap();
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut app =
+        headless::app_with_replay(replay.tick_rate, replay.seed, ReplayMode::Play(replay));
+    for _ in 0..=FRAMES {
+        app.update();
+    }
+    assert_eq!(outcome(&mut app), recorded);
+}
@@ -0,0 +1,30 @@
+use bevy_space::rng::GameRng;
+use rand::Rng;
+
+fn draw(rng: &mut GameRng) -> Vec<u32> {
+    (0..16).map(|_| rng.gameplay().gen()).collect()
+}
+
+#[test]
+fn same_seed_same_sequence() {
+    assert_eq!(draw(&mut GameRng::new(42)), draw(&mut GameRng::new(42)));
+    assert_ne!(draw(&mut GameRng::new(42)), draw(&mut GameRng::new(43)));
+}
+
+#[test]
+fn cosmetic_stream_does_not_affect_gameplay() {
+    let mut rng = GameRng::new(42);
+    for _ in 0..100 {
+        rng.cosmetic().gen::<f32>();
+    }
+    assert_eq!(draw(&mut rng), draw(&mut GameRng::new(42)));
+}
+
+#[test]
+fn reset_restarts_from_seed() {
+    let mut rng = GameRng::new(7);
+    let first = draw(&mut rng);
+    rng.reset();
+    assert_eq!(rng.seed(), 7);
+    assert_eq!(draw(&mut rng), first);
+}
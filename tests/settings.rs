@@ -0,0 +1,137 @@
+use bevy::window::WindowMode;
+use bevy_space::{
+    action::{Action, ActionEvent},
+    common::*,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    pause::PauseState,
+    settings::{Settings, SettingsItem, SettingsMenu},
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn settings_keep_defaults_left_out() {
+    let settings =
+        Settings::parse("(music_volume: 0.5, window_mode: BorderlessFullscreen)").unwrap();
+    assert_eq!(settings.music_volume, 0.5);
+    assert_eq!(settings.window_mode, WindowMode::BorderlessFullscreen);
+    assert_eq!(settings.master_volume, 1.0);
+    assert!(!settings.show_fps);
+
+    assert_eq!(
+        Settings::parse("(sfx_volume: 3.0)").unwrap().sfx_volume,
+        1.0
+    );
+    assert!(Settings::parse("(gamma: 2.2)").is_err());
+}
+
+#[test]
+fn scale_snapped_to_the_steps() {
+    assert_eq!(Settings::parse("(scale: 1.3)").unwrap().scale, 1.5);
+    assert_eq!(Settings::parse("(scale: 0.8)").unwrap().scale, 0.75);
+    assert_eq!(Settings::parse("(scale: 9.0)").unwrap().scale, 2.0);
+    assert_eq!(Settings::parse("(scale: -1.0)").unwrap().scale, 0.5);
+    assert!(Settings::parse("(scale: inf)").is_err());
+    assert!(SETTINGS_SCALES.contains(&Settings::parse("(scale: 1.0)").unwrap().scale));
+}
+
+#[test]
+fn settings_saved_and_loaded() {
+    let path = std::env::temp_dir().join(format!("bevy_space_settings_{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut settings = Settings::load(path.clone());
+    assert_eq!(settings, Settings::load(path.clone()));
+    settings.step(SettingsItem::MasterVolume, true);
+    settings.step(SettingsItem::WindowMode, false);
+    settings.step(SettingsItem::Scale, false);
+    settings.step(SettingsItem::ShowFps, false);
+    settings.save().unwrap();
+
+    let loaded = Settings::load(path.clone());
+    assert_eq!(loaded, settings);
+    assert!((loaded.master_volume - (1.0 - VOLUME_STEP)).abs() < 1e-6);
+    assert_eq!(loaded.window_mode, WindowMode::BorderlessFullscreen);
+    assert_eq!(loaded.scale, 1.5);
+    assert!(loaded.show_fps);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn volume_steps_wrap_around() {
+    let mut settings = Settings::default();
+    settings.step(SettingsItem::SfxVolume, false);
+    assert_eq!(settings.sfx_volume, 0.0);
+    settings.step(SettingsItem::SfxVolume, true);
+    assert_eq!(settings.sfx_volume, 1.0);
+    assert_eq!(settings.sfx_volume(), 1.0);
+}
+
+#[test]
+fn fire_bound_with_start_stays_on_the_settings_screen() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    press(&mut app, Action::Settings);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Settings);
+
+    // the default gamepad South, both fire and start
+    for _ in 0..2 {
+        app.world_mut().send_event_batch([
+            ActionEvent::button(0, Action::Fire, true),
+            ActionEvent::button(0, Action::Start, true),
+        ]);
+        app.update();
+        app.update();
+        assert_eq!(pause_state(&app), PauseState::Settings);
+    }
+    assert!((app.world().resource::<Settings>().master_volume - VOLUME_STEP).abs() < 1e-6);
+}
+
+#[test]
+fn settings_screen_toggles_fps() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    press(&mut app, Action::Settings);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Settings);
+
+    for _ in 0..5 {
+        press(&mut app, Action::MoveRight);
+    }
+    assert_eq!(
+        app.world().resource::<SettingsMenu>().item(),
+        SettingsItem::ShowFps
+    );
+    press(&mut app, Action::Fire);
+    assert!(app.world().resource::<Settings>().show_fps);
+
+    press(&mut app, Action::Start);
+    run_until(&mut app, |app| pause_state(app) == PauseState::Running);
+    assert!(app.world().resource::<Store>().show_state);
+
+    // kept over a new game
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+    assert!(app.world().resource::<Store>().show_state);
+}
+
+#[test]
+fn info_toggle_kept_over_other_settings() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    app.world_mut().send_event(GameStateEvent::Info);
+    app.update();
+    assert!(app.world().resource::<Store>().show_state);
+
+    app.world_mut().resource_mut::<Settings>().music_volume = 0.5;
+    app.update();
+    assert!(app.world().resource::<Store>().show_state);
+
+    // until show_fps itself is changed
+    app.world_mut().resource_mut::<Settings>().show_fps = true;
+    app.update();
+    app.world_mut().resource_mut::<Settings>().show_fps = false;
+    app.update();
+    assert!(!app.world().resource::<Store>().show_state);
+}
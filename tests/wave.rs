@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienKind},
+    bunker::{formation, Bunker},
+    common::*,
+    game_state::{GameState, GameStateEvent, Store},
+    headless,
+    wave::{CurrentWave, Waves},
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn waves_file() {
+    let waves = Waves::parse(&std::fs::read("assets/waves.ron").unwrap()).unwrap();
+    let built_in = Waves::default();
+    assert_eq!(waves.waves.len(), built_in.waves.len());
+    assert_eq!(waves.waves[0], built_in.waves[0]);
+    for (wave, built_in) in waves.waves.iter().zip(&built_in.waves) {
+        assert_eq!(wave.rows, built_in.rows);
+    }
+    // the armored kinds in the later waves
+    assert!(waves.waves[1].rows.contains(&AlienKind::ArmoredCrab));
+    assert!(waves.waves[2].rows.contains(&AlienKind::ArmoredOctopus));
+    assert!(!waves.waves[1].missiles);
+    assert!(waves.waves[2].missiles);
+
+    assert!(Waves::parse(b"(waves: [])").is_err());
+    assert!(Waves::parse(b"(waves: [(rows: [Squid])])").is_err());
+}
+
+#[test]
+fn waves_file_rejects_invalid_numbers() {
+    // the first wave of the file, with the setting changed
+    let content = std::fs::read_to_string("assets/waves.ron").unwrap();
+    let wave = |setting: &str, value: &str| {
+        let (before, after) = content.split_once(&format!("{}: ", setting)).unwrap();
+        let end = after.find(',').unwrap();
+        let content = format!("{}{}: {}{}", before, setting, value, &after[end..]);
+        Waves::parse(content.as_bytes())
+    };
+    assert!(wave("speed", "30.0").is_ok());
+    assert!(wave("mystery_ship_interval", "None").is_ok());
+    assert!(wave("speed", "-30.0").is_err());
+    assert!(wave("speed_kill", "NaN").is_err());
+    assert!(wave("bullet_interval", "0.0").is_err());
+    assert!(wave("bullet_interval", "-0.25").is_err());
+    assert!(wave("start_height", "inf").is_err());
+    assert!(wave("mystery_ship_interval", "Some(-1.0)").is_err());
+    assert!(wave("bunkers", &(BUNKERS_MAX + 1).to_string()).is_err());
+}
+
+#[test]
+fn bunkers_spread_over_the_scene() {
+    for bunkers in [1, BUNKERS, BUNKERS_MAX] {
+        let blocks = formation(bunkers);
+        assert!(blocks
+            .iter()
+            .all(|block| block.translation.x.abs() + 8.0 <= SCENE_WIDTH));
+    }
+}
+
+#[test]
+fn aliens_left_of_the_wave() {
+    let wave = Waves::default().get(1);
+    let mut store = Store::default();
+    assert_eq!(store.aliens_left(&wave), wave.aliens_total());
+    store.aliens_killed = 3;
+    assert_eq!(store.aliens_left(&wave), wave.aliens_total() - 3);
+    // e.g., the waves edited in between
+    store.aliens_killed = wave.aliens_total() + 1;
+    assert_eq!(store.aliens_left(&wave), 0);
+}
+
+#[test]
+fn waves_past_the_last() {
+    let waves = Waves::default();
+    let last = waves.get(3);
+    let next = waves.get(5);
+    assert_eq!(next.rows, last.rows);
+    assert_eq!(next.speed, last.speed + 2.0 * ALIENS_SPEED_WAVE);
+    assert!(next.bullet_interval < last.bullet_interval);
+    assert!(next.missiles);
+}
+
+#[test]
+fn wave_sets_formation_and_bunkers() {
+    let mut app = headless::app(headless::HEADLESS_TICK_RATE);
+    app.update();
+    let mut waves = app.world_mut().resource_mut::<Waves>();
+    waves.waves[0].rows = vec![AlienKind::Crab, AlienKind::Octopus];
+    waves.waves[0].columns = 3;
+    waves.waves[0].bunkers = 0;
+    waves.waves[0].speed = 42.0;
+
+    app.world_mut().send_event(GameStateEvent::PressPlay);
+    run_until(&mut app, |app| state(app) == GameState::Play);
+
+    assert_eq!(count::<With<Alien>>(&mut app), 6);
+    assert_eq!(count::<With<Bunker>>(&mut app), 0);
+    assert_eq!(app.world().resource::<CurrentWave>().aliens_total(), 6);
+    assert_eq!(app.world().resource::<Store>().alien_speed, 42.0);
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy_space::{
+    alien::{Alien, AlienBullet, AlienKilledEvent},
+    bunker::Bunker,
+    common::*,
+    config::GameConfig,
+    headless,
+    lazer::{FireLazerEvent, Lazer},
+    player::Player,
+    weapon::{PowerUp, Weapon},
+};
+
+mod common;
+use common::*;
+
+fn weapon(app: &mut App) -> Weapon {
+    let world = app.world_mut();
+    *world
+        .query_filtered::<&Weapon, With<Player>>()
+        .single(world)
+}
+
+fn fired(app: &mut App) -> usize {
+    count::<With<Lazer>>(app)
+}
+
+fn power_ups(app: &mut App) -> Vec<(Entity, Weapon)> {
+    let world = app.world_mut();
+    world
+        .query::<(Entity, &PowerUp)>()
+        .iter(world)
+        .map(|(entity, power_up)| (entity, power_up.0))
+        .collect()
+}
+
+#[test]
+fn weapon_shots() {
+    assert_eq!(Weapon::Single.shots().len(), 1);
+    let double = Weapon::Double.shots();
+    assert_eq!(double.len(), 2);
+    assert_eq!(double[0].offset, -double[1].offset);
+    let spread = Weapon::Spread.shots();
+    assert_eq!(spread.len(), 3);
+    assert!(spread[0].direction.x < 0.0 && spread[2].direction.x > 0.0);
+    assert!(Weapon::Piercing.shots()[0].piercing);
+}
+
+#[test]
+fn double_cannon_fires_two_lazers() {
+    let mut app = play();
+    // in between bunkers
+    let world = app.world_mut();
+    let (mut transform, mut weapon) = world
+        .query_filtered::<(&mut Transform, &mut Weapon), With<Player>>()
+        .single_mut(world);
+    transform.translation.x = BUNKER_SPACE;
+    *weapon = Weapon::Double;
+
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(fired(&mut app), 2);
+
+    // no more shots until both are clear of the cannon
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(fired(&mut app), 2);
+}
+
+#[test]
+fn volleys_up_to_max_lazers() {
+    let mut app = play();
+    app.world_mut().resource_mut::<GameConfig>().max_lazers = 2;
+    hold_fire(&mut app);
+    despawn_all::<With<AlienBullet>>(&mut app);
+    // nothing to hit above the cannon, the other aliens left for the wave to go on
+    despawn_all::<With<Bunker>>(&mut app);
+    let world = app.world_mut();
+    let in_line: Vec<Entity> = world
+        .query_filtered::<(Entity, &GlobalTransform), With<Alien>>()
+        .iter(world)
+        .filter(|(_, transform)| (transform.translation().x - BUNKER_SPACE).abs() < ALIENS_SPACE)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in in_line {
+        world.despawn(entity);
+    }
+    let world = app.world_mut();
+    let mut transform = world
+        .query_filtered::<&mut Transform, With<Player>>()
+        .single_mut(world);
+    transform.translation.x = BUNKER_SPACE;
+    let player_y = transform.translation.y;
+
+    let clear = |app: &mut App| {
+        let world = app.world_mut();
+        world
+            .query_filtered::<&Transform, With<Lazer>>()
+            .iter(world)
+            .all(|transform| transform.translation.y > player_y + LAZER_SPACING)
+    };
+
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(fired(&mut app), 1);
+    run_until(&mut app, clear);
+
+    // a second volley, the first still in flight
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(fired(&mut app), 2);
+    run_until(&mut app, clear);
+
+    // capped, as long as both are in flight
+    app.world_mut().send_event(FireLazerEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(fired(&mut app), 2);
+}
+
+#[test]
+fn power_up_dropped_picked_up_and_timed_out() {
+    let mut app = play();
+    let world = app.world_mut();
+    let mut config = world.resource_mut::<GameConfig>();
+    config.power_up_probability = 1.0;
+    config.power_up_duration = 1.0;
+
+    // dropped right above the player
+    let player = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation;
+    world.send_event(AlienKilledEvent {
+        position: player.truncate() + Vec2::new(0.0, PLAYER_SIZE.y * 2.0),
+    });
+    app.update();
+    let dropped = power_ups(&mut app);
+    assert_eq!(dropped.len(), 1);
+    let (entity, power_up) = dropped[0];
+    assert_ne!(power_up, Weapon::Single);
+
+    run_until(&mut app, |app| app.world().get_entity(entity).is_none());
+    assert_eq!(weapon(&mut app), power_up);
+
+    // back to the single cannon after the duration
+    let mut frames = 0;
+    while weapon(&mut app) != Weapon::Single && frames < MAX_FRAMES {
+        app.update();
+        frames += 1;
+    }
+    assert!(frames.abs_diff(headless::HEADLESS_TICK_RATE as usize) <= 2);
+}